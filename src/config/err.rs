@@ -0,0 +1,71 @@
+use std::{error, fmt, io};
+
+/// Config Error
+pub struct Error {
+    kind: ErrorKind,
+}
+
+impl Error {
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Config Error: {}!", self.kind)
+    }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Config Error: {}!", self.kind)
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(value: ErrorKind) -> Self {
+        Self { kind: value }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        ErrorKind::Io(value).into()
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Types of Config Errors
+pub enum ErrorKind {
+    /// The config file could not be read
+    Io(io::Error),
+    /// A line that isn't a comment or a `key = value` pair, holds the line number
+    MalformedLine(usize),
+    UnknownKey(String),
+    InvalidValue {
+        key: String,
+        value: String,
+    },
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::Io(err) => write!(f, "failed to read config file ({err})"),
+            ErrorKind::MalformedLine(line) => write!(f, "line {line} is not a `key = value` pair"),
+            ErrorKind::UnknownKey(key) => write!(f, "unknown key `{key}`"),
+            ErrorKind::InvalidValue { key, value } => {
+                write!(f, "invalid value `{value}` for key `{key}`")
+            }
+        }
+    }
+}
@@ -0,0 +1,355 @@
+mod err;
+
+use std::{
+    collections::HashMap,
+    fs,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    str::FromStr,
+    thread,
+    time::Duration,
+};
+
+use crate::{ecs::ErrorAction, log::Level};
+
+pub use err::{Error, ErrorKind};
+
+/// Settings used by the engine for its whole lifetime
+///
+/// Config files are made of `key = value` lines, lines starting with `#` are comments
+/// and any key that is left out keeps its default value
+///
+/// ```text
+/// # number of worker threads shared by every scene
+/// worker_count = 8
+/// # physics frames per second
+/// physics_rate = 60
+/// # frames per second, or "none" to run as fast as possible
+/// max_frame_rate = 144
+/// # physics frames that can run in one frame to catch up after a slow frame
+/// max_catch_up_steps = 5
+/// # frames that profiler timing summaries are averaged over
+/// profiler_window = 120
+/// # seconds that engine stats are averaged over
+/// stats_window = 1.0
+/// # the lowest level of log messages that are shown
+/// log_level = info
+/// # the log level of a single subsystem
+/// log_level.scene = debug
+/// # what happens after an error: log, disable_system or abort
+/// error_action = disable_system
+/// # check that systems only get components in their signature, on by default in debug builds
+/// check_component_access = true
+/// # a file of input action bindings to load
+/// input_bindings = input.cfg
+/// # run systems in the same order every frame, recording and replaying turn this on
+/// deterministic = false
+/// # a file to record every frame's input to
+/// record_input = recording.txt
+/// # a recording to replay instead of taking input
+/// replay_input = recording.txt
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct EngineConfig {
+    pub worker_count: usize,
+    pub physics_rate: f64,
+    pub max_frame_rate: Option<f64>,
+    pub max_catch_up_steps: u32,
+    pub profiler_window: usize,
+    pub stats_window: Duration,
+    pub log_level: Level,
+    /// Log levels of specific targets, overriding log_level
+    pub log_levels: HashMap<String, Level>,
+    /// What the default error handler does with every error
+    pub error_action: ErrorAction,
+    /// Makes getting a component outside of the running system's signature an error
+    pub check_component_access: bool,
+    /// Action bindings to load when the engine is created
+    pub input_bindings: Option<PathBuf>,
+    /// Runs systems in the same order every frame, see Engine::is_deterministic()
+    pub deterministic: bool,
+    pub record_input: Option<PathBuf>,
+    pub replay_input: Option<PathBuf>,
+}
+
+impl EngineConfig {
+    /// Loads a config from a file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// Checks that every setting is in range, the same way config files are checked
+    ///
+    /// # Errors
+    /// Returns an error for the first setting that is out of range
+    pub fn validate(&self) -> Result<(), Error> {
+        let invalid_value = |key: &str, value: String| -> Result<(), Error> {
+            Err(ErrorKind::InvalidValue {
+                key: key.to_string(),
+                value,
+            }
+            .into())
+        };
+
+        if self.worker_count == 0 {
+            return invalid_value("worker_count", self.worker_count.to_string());
+        }
+        if !is_valid_rate(self.physics_rate) {
+            return invalid_value("physics_rate", self.physics_rate.to_string());
+        }
+        if let Some(rate) = self.max_frame_rate.filter(|rate| !is_valid_rate(*rate)) {
+            return invalid_value("max_frame_rate", rate.to_string());
+        }
+        if self.max_catch_up_steps == 0 {
+            return invalid_value("max_catch_up_steps", self.max_catch_up_steps.to_string());
+        }
+        if self.profiler_window == 0 {
+            return invalid_value("profiler_window", self.profiler_window.to_string());
+        }
+        if self.stats_window.is_zero() {
+            return invalid_value("stats_window", self.stats_window.as_secs_f64().to_string());
+        }
+
+        Ok(())
+    }
+
+    /// The time between physics frames
+    pub fn physics_interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.physics_rate)
+    }
+
+    /// The shortest time a frame can take, if the frame rate is limited
+    pub fn min_frame_time(&self) -> Option<Duration> {
+        self.max_frame_rate
+            .map(|rate| Duration::from_secs_f64(1.0 / rate))
+    }
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            worker_count: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            physics_rate: 60.0,
+            max_frame_rate: None,
+            max_catch_up_steps: 5,
+            profiler_window: 120,
+            stats_window: Duration::from_secs(1),
+            log_level: Level::Info,
+            log_levels: HashMap::new(),
+            error_action: ErrorAction::default(),
+            check_component_access: cfg!(debug_assertions),
+            input_bindings: None,
+            deterministic: false,
+            record_input: None,
+            replay_input: None,
+        }
+    }
+}
+
+impl FromStr for EngineConfig {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = Self::default();
+
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(Error::from(ErrorKind::MalformedLine(index + 1)))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            let invalid_value = || -> Error {
+                ErrorKind::InvalidValue {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                }
+                .into()
+            };
+
+            match key {
+                "worker_count" => {
+                    config.worker_count = value
+                        .parse()
+                        .ok()
+                        .filter(|&count| count > 0)
+                        .ok_or_else(invalid_value)?;
+                }
+                "physics_rate" => {
+                    config.physics_rate = parse_rate(value).ok_or_else(invalid_value)?;
+                }
+                "max_frame_rate" => {
+                    config.max_frame_rate = if value == "none" {
+                        None
+                    } else {
+                        Some(parse_rate(value).ok_or_else(invalid_value)?)
+                    };
+                }
+                "max_catch_up_steps" => {
+                    config.max_catch_up_steps = value
+                        .parse()
+                        .ok()
+                        .filter(|&steps| steps > 0)
+                        .ok_or_else(invalid_value)?;
+                }
+                "profiler_window" => {
+                    config.profiler_window = value
+                        .parse()
+                        .ok()
+                        .filter(|&window| window > 0)
+                        .ok_or_else(invalid_value)?;
+                }
+                "stats_window" => {
+                    // windows that don't fit in a Duration or round down to zero are rejected
+                    config.stats_window = value
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(|window| Duration::try_from_secs_f64(window).ok())
+                        .filter(|window| !window.is_zero())
+                        .ok_or_else(invalid_value)?;
+                }
+                "log_level" => {
+                    config.log_level = value.parse().map_err(|_| invalid_value())?;
+                }
+                "error_action" => {
+                    config.error_action = value.parse().map_err(|_| invalid_value())?;
+                }
+                "check_component_access" => {
+                    config.check_component_access = value.parse().map_err(|_| invalid_value())?;
+                }
+                "input_bindings" => {
+                    config.input_bindings = Some(PathBuf::from(value));
+                }
+                "deterministic" => {
+                    config.deterministic = value.parse().map_err(|_| invalid_value())?;
+                }
+                "record_input" => {
+                    config.record_input = Some(PathBuf::from(value));
+                }
+                "replay_input" => {
+                    config.replay_input = Some(PathBuf::from(value));
+                }
+                _ if key.starts_with("log_level.") => {
+                    let target = &key["log_level.".len()..];
+                    let level = value.parse().map_err(|_| invalid_value())?;
+                    config.log_levels.insert(target.to_string(), level);
+                }
+                _ => return Err(ErrorKind::UnknownKey(key.to_string()).into()),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Parses a rate in hertz, which has to be a positive, finite number
+fn parse_rate(value: &str) -> Option<f64> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|rate| is_valid_rate(*rate))
+}
+
+/// Checks that a rate is positive and finite, and that its interval fits in a Duration
+fn is_valid_rate(rate: f64) -> bool {
+    rate.is_finite() && rate > 0.0 && Duration::try_from_secs_f64(1.0 / rate).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_is_valid() {
+        assert!(EngineConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_settings() {
+        let configs = [
+            EngineConfig {
+                worker_count: 0,
+                ..Default::default()
+            },
+            EngineConfig {
+                physics_rate: f64::NAN,
+                ..Default::default()
+            },
+            EngineConfig {
+                physics_rate: -60.0,
+                ..Default::default()
+            },
+            EngineConfig {
+                max_frame_rate: Some(0.0),
+                ..Default::default()
+            },
+            EngineConfig {
+                profiler_window: 0,
+                ..Default::default()
+            },
+            EngineConfig {
+                stats_window: Duration::ZERO,
+                ..Default::default()
+            },
+        ];
+
+        for config in configs {
+            let err = config.validate().unwrap_err();
+            assert!(
+                matches!(err.kind(), ErrorKind::InvalidValue { .. }),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_config_files() {
+        let config = "# comment\nworker_count = 3\nmax_frame_rate = none\nlog_level.scene = debug"
+            .parse::<EngineConfig>()
+            .unwrap();
+
+        assert_eq!(config.worker_count, 3);
+        assert_eq!(config.max_frame_rate, None);
+        assert_eq!(config.log_levels.get("scene"), Some(&Level::Debug));
+
+        assert!(matches!(
+            "worker_count = 0"
+                .parse::<EngineConfig>()
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidValue { .. }
+        ));
+        for window in ["1e30", "-1", "0", "1e-12", "nan", "inf"] {
+            let err = format!("stats_window = {window}")
+                .parse::<EngineConfig>()
+                .unwrap_err();
+            assert!(
+                matches!(err.kind(), ErrorKind::InvalidValue { .. }),
+                "{window}"
+            );
+        }
+        assert_eq!(
+            "stats_window = 0.5"
+                .parse::<EngineConfig>()
+                .unwrap()
+                .stats_window,
+            Duration::from_millis(500)
+        );
+        assert!(matches!(
+            "frame_rate = 60"
+                .parse::<EngineConfig>()
+                .unwrap_err()
+                .kind(),
+            ErrorKind::UnknownKey(_)
+        ));
+        assert!(matches!(
+            "worker_count 3".parse::<EngineConfig>().unwrap_err().kind(),
+            ErrorKind::MalformedLine(1)
+        ));
+    }
+}
@@ -1,8 +1,8 @@
-use std::any::{Any, TypeId};
+use std::any::{type_name, Any, TypeId};
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
-use std::sync::Mutex;
+use std::sync::{Mutex, OnceLock};
 
 use super::{Entity, Error, ErrorKind};
 
@@ -15,6 +15,20 @@ use super::{Entity, Error, ErrorKind};
 /// be equivalent
 pub type Component = TypeId;
 
+/// The type name of every component registered in any scene
+///
+/// A Component is only a TypeId, which can't name its type in errors
+static COMPONENT_NAMES: OnceLock<Mutex<HashMap<Component, &'static str>>> = OnceLock::new();
+
+/// The type name of a component, if it has been registered in any scene
+pub(crate) fn component_name(component: &Component) -> Option<&'static str> {
+    let names = COMPONENT_NAMES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap();
+    names.get(component).copied()
+}
+
 pub struct UnsafeComponentCell<'a, C> {
     data: *mut C,
     _owns: PhantomData<&'a mut C>,
@@ -36,26 +50,27 @@ impl<'a, C> DerefMut for UnsafeComponentCell<'a, C> {
 
 // See https://ianjk.com/ecs-in-rust/ for more details
 trait ComponentArray {
-    fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
-    fn clear(&mut self);
     fn remove_component(&mut self, entity: &Entity) -> Result<(), Error>;
     fn has_entity_data(&self, entity: &Entity) -> bool;
+    /// Moves an entity's component, if it has one, into another array of the same type
+    fn move_component(
+        &mut self,
+        entity: &Entity,
+        target: &mut dyn ComponentArray,
+        target_entity: &Entity,
+    ) -> Result<(), Error>;
+    /// Creates an empty array that stores the same component type
+    fn new_empty(&self) -> Box<dyn ComponentArray + Send>;
+    fn len(&self) -> usize;
+    fn component_type_name(&self) -> &'static str;
 }
 
 impl<T: Send + 'static> ComponentArray for HashMap<Entity, T> {
-    fn as_any(&self) -> &dyn Any {
-        self as &dyn Any
-    }
-
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self as &mut dyn Any
     }
 
-    fn clear(&mut self) {
-        self.clear();
-    }
-
     fn remove_component(&mut self, entity: &Entity) -> Result<(), Error> {
         self.remove(entity);
         Ok(())
@@ -64,6 +79,35 @@ impl<T: Send + 'static> ComponentArray for HashMap<Entity, T> {
     fn has_entity_data(&self, entity: &Entity) -> bool {
         self.contains_key(entity)
     }
+
+    fn move_component(
+        &mut self,
+        entity: &Entity,
+        target: &mut dyn ComponentArray,
+        target_entity: &Entity,
+    ) -> Result<(), Error> {
+        let target = target
+            .as_any_mut()
+            .downcast_mut::<HashMap<Entity, T>>()
+            .ok_or(Error::from(ErrorKind::ComponentArrayDowncastFailure))?;
+
+        if let Some(component) = self.remove(entity) {
+            target.insert(target_entity.clone(), component);
+        }
+        Ok(())
+    }
+
+    fn new_empty(&self) -> Box<dyn ComponentArray + Send> {
+        Box::<HashMap<Entity, T>>::default()
+    }
+
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+
+    fn component_type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
 }
 
 pub struct ComponentManager {
@@ -94,10 +138,14 @@ impl ComponentManager {
                 v.insert(entity.clone(), component);
                 Ok(())
             } else {
-                Err(ErrorKind::ComponentArrayDowncastFailure.into())
+                Err(Error::from(ErrorKind::ComponentArrayDowncastFailure)
+                    .with_component(type_name::<C>())
+                    .with_entity(entity))
             }
         } else {
-            Err(ErrorKind::ComponentNotRegistered.into())
+            Err(Error::from(ErrorKind::ComponentNotRegistered)
+                .with_component(type_name::<C>())
+                .with_entity(entity))
         }
     }
 
@@ -113,10 +161,14 @@ impl ComponentManager {
                 v.remove(entity);
                 Ok(())
             } else {
-                Err(ErrorKind::ComponentArrayDowncastFailure.into())
+                Err(Error::from(ErrorKind::ComponentArrayDowncastFailure)
+                    .with_component(type_name::<C>())
+                    .with_entity(entity))
             }
         } else {
-            Err(ErrorKind::ComponentNotRegistered.into())
+            Err(Error::from(ErrorKind::ComponentNotRegistered)
+                .with_component(type_name::<C>())
+                .with_entity(entity))
         }
     }
 
@@ -128,6 +180,59 @@ impl ComponentManager {
         Ok(())
     }
 
+    /// Moves all of an entity's components into another component manager,
+    /// registering any component types the target is missing
+    ///
+    /// The two component managers must be different
+    pub fn move_components(
+        &self,
+        entity: &Entity,
+        target: &ComponentManager,
+        target_entity: &Entity,
+    ) -> Result<(), Error> {
+        // managers are locked in address order, so transfers in opposite directions can't deadlock
+        let (components, mut target_components) =
+            if std::ptr::from_ref(self) < std::ptr::from_ref(target) {
+                let components = self.components.lock().unwrap();
+                (components, target.components.lock().unwrap())
+            } else {
+                let target_components = target.components.lock().unwrap();
+                (self.components.lock().unwrap(), target_components)
+            };
+
+        for (type_id, comp_arr) in components.iter() {
+            let mut comp_arr = comp_arr.lock().unwrap();
+
+            if !comp_arr.has_entity_data(entity) {
+                continue;
+            }
+
+            let target_arr = target_components
+                .entry(*type_id)
+                .or_insert_with(|| Mutex::new(comp_arr.new_empty()));
+
+            comp_arr.move_component(
+                entity,
+                target_arr.get_mut().unwrap().as_mut(),
+                target_entity,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Counts how many entities own each registered component type, keyed by type name
+    pub fn component_counts(&self) -> HashMap<&'static str, usize> {
+        self.components
+            .lock()
+            .unwrap()
+            .values()
+            .map(|comp_arr| {
+                let comp_arr = comp_arr.lock().unwrap();
+                (comp_arr.component_type_name(), comp_arr.len())
+            })
+            .collect()
+    }
+
     /// Checks if an entity has all the given components
     pub fn has_components(&self, entity: &Entity, components: &[Component]) -> Result<bool, Error> {
         for comp in components {
@@ -146,6 +251,11 @@ impl ComponentManager {
     /// Registers a new component for use with the component manager
     pub fn register_component<C: Send + 'static>(&self) -> Component {
         let type_id = TypeId::of::<C>();
+        COMPONENT_NAMES
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap()
+            .insert(type_id, type_name::<C>());
 
         if self.is_component_registered(&type_id) {
             // type cast is redundant, but it makes the code intention easier to see
@@ -192,13 +302,19 @@ impl ComponentManager {
 
                     Ok(unsafe_cell)
                 } else {
-                    Err(ErrorKind::ComponentArrayDowncastFailure.into())
+                    Err(Error::from(ErrorKind::ComponentArrayDowncastFailure)
+                        .with_component(type_name::<C>())
+                        .with_entity(entity))
                 }
             } else {
-                Err(ErrorKind::EntityDoesNotOwnComponent.into())
+                Err(Error::from(ErrorKind::EntityDoesNotOwnComponent)
+                    .with_component(type_name::<C>())
+                    .with_entity(entity))
             }
         } else {
-            Err(ErrorKind::ComponentNotRegistered.into())
+            Err(Error::from(ErrorKind::ComponentNotRegistered)
+                .with_component(type_name::<C>())
+                .with_entity(entity))
         }
     }
 }
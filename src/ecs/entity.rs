@@ -1,5 +1,5 @@
 use super::{Error, ErrorKind};
-use std::cell::RefCell;
+use std::sync::Mutex;
 use std::{collections::VecDeque, hash::Hash};
 
 /// An Entity Id, guaranteed to be unique from all the entities
@@ -31,52 +31,83 @@ impl Entity {
     pub(crate) fn clone(&self) -> Entity {
         Self(self.0)
     }
+
+    /// The raw id of the entity, only used for printing
+    pub(crate) fn id(&self) -> u32 {
+        self.0
+    }
+}
+
+/// The ids an entity manager has handed out
+struct EntityIds {
+    next_entity_id: u32,
+    dead_entities: VecDeque<u32>,
 }
 
+/// Creates and destroys entities, safe to use from systems running on several threads
 pub struct EntityManager {
-    next_entity_id: RefCell<u32>,
-    dead_entities: RefCell<VecDeque<u32>>,
+    ids: Mutex<EntityIds>,
 }
 
 impl EntityManager {
     pub fn new() -> Self {
         Self {
-            next_entity_id: RefCell::new(u32::MIN),
-            dead_entities: RefCell::new(VecDeque::new()),
+            ids: Mutex::new(EntityIds {
+                next_entity_id: u32::MIN,
+                dead_entities: VecDeque::new(),
+            }),
         }
     }
 
     /// Creates a new entity, unique to this entity manager
     pub fn create_entity(&self) -> Result<Entity, Error> {
-        if !self.dead_entities.borrow().is_empty() {
-            return Ok(Entity(self.dead_entities.borrow_mut().pop_front().unwrap()));
+        let mut ids = self.ids.lock().unwrap();
+
+        if let Some(id) = ids.dead_entities.pop_front() {
+            return Ok(Entity(id));
         }
 
-        if *self.next_entity_id.borrow() == u32::MAX {
+        if ids.next_entity_id == u32::MAX {
             return Err(ErrorKind::EntityMaxReached.into());
         }
 
-        *self.next_entity_id.borrow_mut() += 1;
-        Ok(Entity(*self.next_entity_id.borrow() - 1))
+        ids.next_entity_id += 1;
+        Ok(Entity(ids.next_entity_id - 1))
     }
 
     /// Destroys an entity if it hasn't been already
     pub fn destroy_entity(&self, entity: Entity) {
-        if self.does_entity_exist(&entity) {
-            self.dead_entities.borrow_mut().push_back(entity.0);
+        let mut ids = self.ids.lock().unwrap();
+
+        if ids.contains(&entity) {
+            ids.dead_entities.push_back(entity.0);
         }
     }
 
     /// Retrieves all living entities from this entity manager
     pub fn get_living_entities(&self) -> Vec<Entity> {
-        (0..*self.next_entity_id.borrow())
-            .filter(|e| !self.dead_entities.borrow().contains(e))
+        let ids = self.ids.lock().unwrap();
+
+        (0..ids.next_entity_id)
+            .filter(|e| !ids.dead_entities.contains(e))
             .map(Entity)
             .collect()
     }
 
+    /// Counts the living entities in this entity manager
+    pub fn living_entity_count(&self) -> usize {
+        let ids = self.ids.lock().unwrap();
+        ids.next_entity_id as usize - ids.dead_entities.len()
+    }
+
     /// Checks if an entity exists in the entity manager
     pub fn does_entity_exist(&self, entity: &Entity) -> bool {
-        *self.next_entity_id.borrow() > entity.0 && !self.dead_entities.borrow().contains(&entity.0)
+        self.ids.lock().unwrap().contains(entity)
+    }
+}
+
+impl EntityIds {
+    fn contains(&self, entity: &Entity) -> bool {
+        self.next_entity_id > entity.0 && !self.dead_entities.contains(&entity.0)
     }
 }
@@ -1,31 +1,194 @@
-use std::{error, fmt};
+use std::{error, fmt, str::FromStr};
+
+use super::Entity;
+use crate::scene::Scene;
 
 /// Ecs Error
+///
+/// Besides its kind, an error can carry the system, scene, entity and component
+/// it happened with, which are shown when the error is printed, and the error
+/// that caused it, which is only returned by source() and isn't part of the message
 pub struct Error {
     kind: ErrorKind,
+    system: Option<&'static str>,
+    scene: Option<Scene>,
+    entity: Option<Entity>,
+    component: Option<&'static str>,
+    source: Option<Box<dyn error::Error + Send + Sync>>,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind) -> Self {
+        Self {
+            kind,
+            system: None,
+            scene: None,
+            entity: None,
+            component: None,
+            source: None,
+        }
+    }
+
+    /// Creates an error for a failure outside of the ecs, such as a system failing to load a file
+    pub fn other<E>(source: E) -> Self
+    where
+        E: Into<Box<dyn error::Error + Send + Sync>>,
+    {
+        Self::new(ErrorKind::Other).with_source(source)
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// The type name of the system that caused the error, if it came from a system
+    pub fn system(&self) -> Option<&'static str> {
+        self.system
+    }
+
+    /// The scene the error happened in, if it is known
+    pub fn scene(&self) -> Option<Scene> {
+        self.scene
+    }
+
+    /// The entity the error happened with, if there is one
+    pub fn entity(&self) -> Option<&Entity> {
+        self.entity.as_ref()
+    }
+
+    /// The type name of the component the error happened with, if there is one
+    pub fn component(&self) -> Option<&'static str> {
+        self.component
+    }
+
+    /// Adds the system that caused the error, unless the error already has one
+    pub fn with_system(mut self, system: &'static str) -> Self {
+        self.system.get_or_insert(system);
+        self
+    }
+
+    /// Adds the scene the error happened in, unless the error already has one
+    pub fn with_scene(mut self, scene: Scene) -> Self {
+        self.scene.get_or_insert(scene);
+        self
+    }
+
+    /// Adds the entity the error happened with, unless the error already has one
+    pub fn with_entity(mut self, entity: &Entity) -> Self {
+        self.entity.get_or_insert_with(|| entity.clone());
+        self
+    }
+
+    /// Adds the component the error happened with, unless the error already has one
+    pub fn with_component(mut self, component: &'static str) -> Self {
+        self.component.get_or_insert(component);
+        self
+    }
+
+    /// Sets the error that caused this error, replacing the current cause
+    pub fn with_source<E>(mut self, source: E) -> Self
+    where
+        E: Into<Box<dyn error::Error + Send + Sync>>,
+    {
+        self.source = Some(source.into());
+        self
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "ECS Error: {}!", self.kind)
+        write!(f, "ECS Error [E{:03}]: {}!", self.kind.code(), self.kind)?;
+
+        let mut context = Vec::new();
+        if let Some(system) = self.system {
+            context.push(format!("system: {system}"));
+        }
+        if let Some(scene) = &self.scene {
+            context.push(format!("scene: {scene:?}"));
+        }
+        if let Some(entity) = &self.entity {
+            context.push(format!("entity: {}", entity.id()));
+        }
+        if let Some(component) = self.component {
+            context.push(format!("component: {component}"));
+        }
+
+        if !context.is_empty() {
+            write!(f, " ({})", context.join(", "))?;
+        }
+
+        Ok(())
     }
 }
 
 impl fmt::Debug for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "ECS Error: {}!", self.kind)
+        fmt::Display::fmt(self, f)
     }
 }
 
 impl From<ErrorKind> for Error {
     fn from(value: ErrorKind) -> Self {
-        Self { kind: value }
+        Self::new(value)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn error::Error + 'static))
+    }
+}
+
+/// What the engine does after an error
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ErrorAction {
+    /// The error is logged and the engine carries on
+    Log,
+    /// The error is logged and the system that caused it stops running,
+    /// errors that didn't come from a system are only logged
+    #[default]
+    DisableSystem,
+    /// The error is logged and the engine stops, returning the error from Engine::run()
+    Abort,
+}
+
+impl ErrorAction {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            ErrorAction::Log => "log",
+            ErrorAction::DisableSystem => "disable_system",
+            ErrorAction::Abort => "abort",
+        }
+    }
+}
+
+impl fmt::Display for ErrorAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
     }
 }
 
-impl error::Error for Error {}
+impl FromStr for ErrorAction {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "log" => Ok(ErrorAction::Log),
+            "disable_system" => Ok(ErrorAction::DisableSystem),
+            "abort" => Ok(ErrorAction::Abort),
+            _ => Err(()),
+        }
+    }
+}
 
 /// Types of Ecs Errors
+///
+/// Every kind has a code that stays the same between versions,
+/// so it can be looked up or matched on in logs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum ErrorKind {
     EntityMaxReached,
     EntityDoesNotExist,
@@ -35,6 +198,11 @@ pub enum ErrorKind {
     SceneMaxReached,
     SceneDoesNotExist,
     NoCurrentScene,
+    SystemPanicked,
+    ComponentNotInSignature,
+    TimerDoesNotExist,
+    /// A failure outside of the ecs, the cause is the error's source
+    Other,
 }
 
 impl ErrorKind {
@@ -48,6 +216,30 @@ impl ErrorKind {
             ErrorKind::SceneMaxReached => "max scene count reached",
             ErrorKind::SceneDoesNotExist => "scene doesn't exist",
             ErrorKind::NoCurrentScene => "there is no current scene",
+            ErrorKind::SystemPanicked => "a system panicked while running",
+            ErrorKind::ComponentNotInSignature => {
+                "a system accessed a component outside of its signature"
+            }
+            ErrorKind::TimerDoesNotExist => "timer doesn't exist in the scene",
+            ErrorKind::Other => "an operation failed",
+        }
+    }
+
+    /// The kind's error code, shown as `E` followed by the code when an error is printed
+    pub fn code(&self) -> u16 {
+        match *self {
+            ErrorKind::EntityMaxReached => 1,
+            ErrorKind::EntityDoesNotExist => 2,
+            ErrorKind::EntityDoesNotOwnComponent => 3,
+            ErrorKind::ComponentNotRegistered => 4,
+            ErrorKind::ComponentArrayDowncastFailure => 5,
+            ErrorKind::SceneMaxReached => 6,
+            ErrorKind::SceneDoesNotExist => 7,
+            ErrorKind::NoCurrentScene => 8,
+            ErrorKind::SystemPanicked => 9,
+            ErrorKind::Other => 10,
+            ErrorKind::ComponentNotInSignature => 11,
+            ErrorKind::TimerDoesNotExist => 12,
         }
     }
 }
@@ -57,3 +249,20 @@ impl fmt::Display for ErrorKind {
         write!(f, "{}", self.as_str())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn causes_are_sources_and_not_part_of_the_message() {
+        let err = Error::other("file not found").with_system("LoadSystem");
+
+        assert_eq!(
+            err.to_string(),
+            "ECS Error [E010]: an operation failed! (system: LoadSystem)"
+        );
+        assert_eq!(err.source().unwrap().to_string(), "file not found");
+    }
+}
@@ -4,11 +4,11 @@ mod err;
 mod system;
 
 pub use component::Component;
-pub(crate) use component::ComponentManager;
 pub use component::UnsafeComponentCell;
+pub(crate) use component::{component_name, ComponentManager};
 pub use entity::Entity;
 pub(crate) use entity::EntityManager;
-pub(crate) use err::*;
+pub use err::{Error, ErrorAction, ErrorKind};
 
-pub(crate) use self::system::SystemManager;
-pub use system::System;
+pub(crate) use self::system::{check_component_access, SystemManager};
+pub use system::{ParallelSystem, System};
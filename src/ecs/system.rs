@@ -1,30 +1,191 @@
 use std::{
-    any::TypeId,
+    any::{type_name, TypeId},
     cell::RefCell,
     collections::HashMap,
-    sync::{Arc, Mutex},
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
     time::Duration,
 };
 
-use super::{Component, Entity};
-use crate::ThreadPool;
+use super::{Component, Entity, Error, ErrorAction, ErrorKind};
+use crate::thread_pool::JobError;
 
+/// The name and signature of a running system
+type RunningSystem = (&'static str, Arc<[Component]>);
+
+thread_local! {
+    /// The system running on this thread, while component access is checked
+    static RUNNING_SYSTEM: RefCell<Option<RunningSystem>> = const { RefCell::new(None) };
+}
+
+/// Checks that the system running on this thread, if any, has the component in its signature
+///
+/// Only systems run while EngineConfig::check_component_access is set are checked
+pub(crate) fn check_component_access<C: 'static>() -> Result<(), Error> {
+    RUNNING_SYSTEM.with_borrow(|running_system| match running_system {
+        Some((name, signature)) if !signature.contains(&TypeId::of::<C>()) => {
+            Err(Error::from(ErrorKind::ComponentNotInSignature)
+                .with_system(name)
+                .with_component(type_name::<C>()))
+        }
+        _ => Ok(()),
+    })
+}
+
+/// Marks a system as running on this thread until it is dropped
+struct RunningSystemGuard {
+    previous: Option<RunningSystem>,
+}
+
+impl RunningSystemGuard {
+    fn new(name: &'static str, signature: &Arc<[Component]>) -> Self {
+        let previous = RUNNING_SYSTEM.replace(Some((name, Arc::clone(signature))));
+        Self { previous }
+    }
+}
+
+impl Drop for RunningSystemGuard {
+    fn drop(&mut self) {
+        RUNNING_SYSTEM.set(self.previous.take());
+    }
+}
+
+/// A system that runs over every entity matching its signature
+///
+/// Errors returned by a system are passed to the engine's error handler,
+/// which decides whether the error is logged, the system is disabled or
+/// the engine stops
 pub trait System: Send {
     /// runs when the scene is loaded
-    fn on_entry(&mut self, _engine: Arc<crate::Engine>, _entity: Entity) {}
+    fn on_entry(&mut self, _engine: Arc<crate::Engine>, _entity: Entity) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// runs when the scene is unloaded
+    fn on_exit(&mut self, _engine: Arc<crate::Engine>, _entity: Entity) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// runs every frame
+    fn on_frame(
+        &mut self,
+        _engine: Arc<crate::Engine>,
+        _entity: Entity,
+        _dt: Duration,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// runs every physics frame (fixed rate)
+    fn on_physics_frame(
+        &mut self,
+        _engine: Arc<crate::Engine>,
+        _entity: Entity,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// A system that is safe to run on many entities at the same time
+///
+/// The entities matching a parallel system are split into chunks
+/// that are spread across every worker thread, which is why its
+/// methods only get shared access to the system
+pub trait ParallelSystem: Send + Sync {
+    /// runs when the scene is loaded
+    fn on_entry(&self, _engine: Arc<crate::Engine>, _entity: Entity) -> Result<(), Error> {
+        Ok(())
+    }
 
     /// runs when the scene is unloaded
-    fn on_exit(&mut self, _engine: Arc<crate::Engine>, _entity: Entity) {}
+    fn on_exit(&self, _engine: Arc<crate::Engine>, _entity: Entity) -> Result<(), Error> {
+        Ok(())
+    }
 
     /// runs every frame
-    fn on_frame(&mut self, _engine: Arc<crate::Engine>, _entity: Entity, _dt: Duration) {}
+    fn on_frame(
+        &self,
+        _engine: Arc<crate::Engine>,
+        _entity: Entity,
+        _dt: Duration,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
 
     /// runs every physics frame (fixed rate)
-    fn on_physics_frame(&mut self, _engine: Arc<crate::Engine>, _entity: Entity) {}
+    fn on_physics_frame(&self, _engine: Arc<crate::Engine>, _entity: Entity) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+enum SystemHandle {
+    /// Runs over its entities on a single thread
+    Single(Arc<Mutex<dyn System>>),
+    /// Runs over its entities on every thread
+    Parallel(Arc<dyn ParallelSystem>),
 }
 
-/// The list of required components and the system itself
-type SystemData = (Vec<Component>, Arc<Mutex<dyn System>>);
+struct SystemData {
+    /// The list of required components
+    signature: Arc<[Component]>,
+    /// The system's type name, used to identify it in the profiler and in errors
+    name: &'static str,
+    system: SystemHandle,
+    /// Set when the error handler disables the system, disabled systems are skipped
+    is_disabled: AtomicBool,
+}
+
+/// The system method to run
+#[derive(Clone, Copy)]
+enum SystemEvent {
+    Entry,
+    Exit,
+    Frame(Duration),
+    PhysicsFrame,
+}
+
+impl SystemEvent {
+    /// The name of the system method the event runs
+    fn method_name(self) -> &'static str {
+        match self {
+            SystemEvent::Entry => "on_entry",
+            SystemEvent::Exit => "on_exit",
+            SystemEvent::Frame(_) => "on_frame",
+            SystemEvent::PhysicsFrame => "on_physics_frame",
+        }
+    }
+
+    fn run(
+        self,
+        system: &mut dyn System,
+        engine: Arc<crate::Engine>,
+        entity: Entity,
+    ) -> Result<(), Error> {
+        match self {
+            SystemEvent::Entry => system.on_entry(engine, entity),
+            SystemEvent::Exit => system.on_exit(engine, entity),
+            SystemEvent::Frame(dt) => system.on_frame(engine, entity, dt),
+            SystemEvent::PhysicsFrame => system.on_physics_frame(engine, entity),
+        }
+    }
+
+    fn run_parallel(
+        self,
+        system: &dyn ParallelSystem,
+        engine: Arc<crate::Engine>,
+        entity: Entity,
+    ) -> Result<(), Error> {
+        match self {
+            SystemEvent::Entry => system.on_entry(engine, entity),
+            SystemEvent::Exit => system.on_exit(engine, entity),
+            SystemEvent::Frame(dt) => system.on_frame(engine, entity, dt),
+            SystemEvent::PhysicsFrame => system.on_physics_frame(engine, entity),
+        }
+    }
+}
 
 pub struct SystemManager {
     systems: Systems,
@@ -39,26 +200,44 @@ impl SystemManager {
 
     /// Registers a system for use in the scene
     pub fn register_system<S: System + 'static>(&self, signature: &[Component], system: S) {
-        self.systems.add_system(signature, system);
+        self.systems.add_system(
+            TypeId::of::<S>(),
+            type_name::<S>(),
+            signature,
+            SystemHandle::Single(Arc::new(Mutex::new(system))),
+        );
+    }
+
+    /// Registers a parallel system for use in the scene
+    pub fn register_parallel_system<S: ParallelSystem + 'static>(
+        &self,
+        signature: &[Component],
+        system: S,
+    ) {
+        self.systems.add_system(
+            TypeId::of::<S>(),
+            type_name::<S>(),
+            signature,
+            SystemHandle::Parallel(Arc::new(system)),
+        );
     }
 
     pub fn on_entry(&self, engine: Arc<crate::Engine>) {
-        self.systems.on_entry(engine);
+        self.systems.on_entry(engine)
     }
 
     pub fn on_exit(&self, engine: Arc<crate::Engine>) {
-        self.systems.on_exit(engine);
+        self.systems.on_exit(engine)
     }
 
-    pub fn on_frame(&self, engine: Arc<crate::Engine>, is_physics_frame: bool, dt: Duration) {
-        self.systems.on_frame(engine, is_physics_frame, dt);
+    pub fn on_frame(&self, engine: Arc<crate::Engine>, physics_steps: u32, dt: Duration) {
+        self.systems.on_frame(engine, physics_steps, dt)
     }
 }
 
 pub struct Systems {
     system_list: Mutex<HashMap<TypeId, SystemData>>,
     system_parallels: Mutex<Vec<Vec<TypeId>>>,
-    t_pool: RefCell<ThreadPool>,
 }
 
 impl Systems {
@@ -66,209 +245,401 @@ impl Systems {
         Self {
             system_list: Mutex::new(HashMap::new()),
             system_parallels: Mutex::new(Vec::new()),
-            t_pool: RefCell::new(ThreadPool::new(4)),
         }
     }
 
-    pub fn add_system<S: System + 'static>(&self, signature: &[Component], system: S) {
-        let system_id = TypeId::of::<S>();
-        let signature = signature.to_vec();
+    fn add_system(
+        &self,
+        system_id: TypeId,
+        name: &'static str,
+        signature: &[Component],
+        system: SystemHandle,
+    ) {
+        let signature: Arc<[Component]> = signature.into();
 
         if self.system_list.lock().unwrap().contains_key(&system_id) {
             // this system has already been added
             return;
         }
 
-        self.system_list
-            .lock()
-            .unwrap()
-            .insert(system_id, (signature.clone(), Arc::new(Mutex::new(system))));
-
+        self.system_list.lock().unwrap().insert(
+            system_id,
+            SystemData {
+                signature,
+                name,
+                system,
+                is_disabled: AtomicBool::new(false),
+            },
+        );
+
+        // locked in the same order as run_systems
         let mut parallels = self.system_parallels.lock().unwrap();
+        let system_list = self.system_list.lock().unwrap();
+        let signature = &system_list[&system_id].signature;
+
+        // systems get mutable access to every component in their signature,
+        // so any shared component means they can't run at the same time
+        let shares_components = |other: &TypeId| {
+            let other = &system_list[other].signature;
+            signature.iter().any(|component| other.contains(component))
+        };
+
+        match parallels
+            .iter_mut()
+            .find(|parallel| !parallel.iter().any(shares_components))
+        {
+            Some(parallel) => parallel.push(system_id),
+            None => parallels.push(vec![system_id]),
+        }
+    }
 
-        let mut is_inserted = false;
-
-        for parallel in parallels.iter_mut() {
-            let mut fits_in_parallel = true;
-
-            for system in parallel.iter() {
-                // check for overlapping components
-                let system_list = self.system_list.lock().unwrap();
-
-                let (components, _) = system_list.get(system).unwrap();
+    pub fn on_entry(&self, engine: Arc<crate::Engine>) {
+        self.run_systems(&engine, SystemEvent::Entry);
+    }
 
-                if components.iter().any(|val| components.contains(val)) {
-                    fits_in_parallel = false;
-                    break;
-                }
-            }
+    pub fn on_exit(&self, engine: Arc<crate::Engine>) {
+        self.run_systems(&engine, SystemEvent::Exit);
+    }
 
-            if fits_in_parallel {
-                parallel.push(system_id);
-                is_inserted = true;
-                break;
-            }
+    pub fn on_frame(&self, engine: Arc<crate::Engine>, physics_steps: u32, dt: Duration) {
+        for _ in 0..physics_steps {
+            self.run_systems(&engine, SystemEvent::PhysicsFrame);
         }
 
-        if !is_inserted {
-            parallels.push(vec![system_id]);
-        }
+        self.run_systems(&engine, SystemEvent::Frame(dt));
     }
 
-    pub fn on_entry(&self, engine: Arc<crate::Engine>) {
+    /// Runs a system method for every entity that matches each system's signature
+    ///
+    /// Systems in the same parallel run at the same time, each parallel
+    /// finishes before the next one starts. Errors and panics are passed to the
+    /// engine's error handler, if it aborts the engine later parallels are skipped
+    fn run_systems(&self, engine: &Arc<crate::Engine>, event: SystemEvent) {
         let parallels = self.system_parallels.lock().unwrap();
         let systems = self.system_list.lock().unwrap();
 
-        for parallel in parallels.iter() {
-            for system_id in parallel {
-                let (reqs, system) = systems.get(system_id).unwrap();
-
-                let entity_list = engine
-                    .scenes()
-                    .get_current_scene()
-                    .unwrap()
-                    .get_living_entities();
-
-                let system_entities = entity_list
-                    .into_iter()
-                    .filter(|e| {
-                        engine
-                            .scenes()
-                            .get_current_scene()
-                            .unwrap()
-                            .has_components(e, reqs)
-                            .unwrap()
-                    })
-                    .collect::<Vec<_>>();
-
-                let engine_handle = Arc::clone(&engine);
-                let system_handle = Arc::clone(system);
-
-                self.t_pool.borrow_mut().execute(move || {
-                    let mut system = system_handle.lock().unwrap();
-                    for entity in system_entities {
-                        system.on_entry(Arc::clone(&engine_handle), entity.clone());
-                    }
+        for (index, parallel) in parallels.iter().enumerate() {
+            if engine.is_aborting() {
+                return;
+            }
+
+            // every batch of a method shares a summary, the trace tells them apart
+            let _batch_span = engine
+                .profiler()
+                .detailed_span(event.method_name(), "batch", || {
+                    format!("{} batch {index}", event.method_name())
                 });
+
+            let batch = parallel
+                .iter()
+                .map(|system_id| systems.get(system_id).unwrap())
+                .filter(|system| !system.is_disabled.load(Ordering::Relaxed));
+
+            if engine.is_deterministic() {
+                // one at a time so entities are created and destroyed in the same order every run
+                batch.for_each(|system| system.run(engine, event));
+                continue;
             }
-            self.t_pool.borrow().wait();
-        }
-    }
 
-    pub fn on_exit(&self, engine: Arc<crate::Engine>) {
-        let parallels = self.system_parallels.lock().unwrap();
-        let systems = self.system_list.lock().unwrap();
+            let result = engine.thread_pool().scope(|s| {
+                for system in batch {
+                    s.spawn(move || system.run(engine, event));
+                }
+            });
 
-        for parallel in parallels.iter() {
-            for system_id in parallel {
-                let (reqs, system) = systems.get(system_id).unwrap();
-
-                let entity_list = engine
-                    .scenes()
-                    .get_current_scene()
-                    .unwrap()
-                    .get_living_entities();
-
-                let system_entities = entity_list
-                    .into_iter()
-                    .filter(|e| {
-                        engine
-                            .scenes()
-                            .get_current_scene()
-                            .unwrap()
-                            .has_components(e, reqs)
-                            .unwrap()
-                    })
-                    .collect::<Vec<_>>();
-
-                let engine_handle = Arc::clone(&engine);
-                let system_handle = Arc::clone(system);
-
-                self.t_pool.borrow_mut().execute(move || {
-                    let mut system = system_handle.lock().unwrap();
-                    for entity in system_entities {
-                        system.on_exit(Arc::clone(&engine_handle), entity.clone());
-                    }
-                });
+            // system panics are caught while running them, so this is a bug in the ecs
+            if let Err(err) = result {
+                err.resume();
             }
-            self.t_pool.borrow().wait();
         }
     }
+}
 
-    pub fn on_frame(&self, engine: Arc<crate::Engine>, is_physics_frame: bool, dt: Duration) {
-        let parallels = self.system_parallels.lock().unwrap();
-        let systems = self.system_list.lock().unwrap();
+impl SystemData {
+    /// Runs the system for every entity in the current scene that matches its signature
+    fn run(&self, engine: &Arc<crate::Engine>, event: SystemEvent) {
+        let _system_span = engine.profiler().detailed_span(self.name, "system", || {
+            format!("{}::{}", self.name, event.method_name())
+        });
+
+        let current_scene = match engine.scenes().get_current_scene() {
+            Ok(current_scene) => current_scene,
+            Err(err) => {
+                self.handle_error(engine, err);
+                return;
+            }
+        };
+
+        let mut system_entities = Vec::new();
+        for entity in current_scene.get_living_entities() {
+            match current_scene.has_components(&entity, &self.signature) {
+                Ok(true) => system_entities.push(entity),
+                Ok(false) => {}
+                Err(err) => {
+                    // the signature is wrong for every entity, so it is only reported once
+                    self.handle_error(engine, err);
+                    return;
+                }
+            }
+        }
 
-        if is_physics_frame {
-            for parallel in parallels.iter() {
-                for system_id in parallel {
-                    let (reqs, system) = systems.get(system_id).unwrap();
-
-                    let entity_list = engine
-                        .scenes()
-                        .get_current_scene()
-                        .unwrap()
-                        .get_living_entities();
-
-                    let system_entities = entity_list
-                        .into_iter()
-                        .filter(|e| {
-                            engine
-                                .scenes()
-                                .get_current_scene()
-                                .unwrap()
-                                .has_components(e, reqs)
-                                .unwrap()
-                        })
-                        .collect::<Vec<_>>();
-
-                    let engine_handle = Arc::clone(&engine);
-                    let system_handle = Arc::clone(system);
-
-                    self.t_pool.borrow_mut().execute(move || {
-                        let mut system = system_handle.lock().unwrap();
-                        for entity in system_entities {
-                            system.on_physics_frame(Arc::clone(&engine_handle), entity.clone());
+        let check_access = engine.config().check_component_access;
+
+        match &self.system {
+            SystemHandle::Single(system) => {
+                let mut system = system.lock().unwrap();
+                let _guard =
+                    check_access.then(|| RunningSystemGuard::new(self.name, &self.signature));
+
+                for entity in system_entities {
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                        event.run(&mut *system, Arc::clone(engine), entity.clone())
+                    }))
+                    .unwrap_or_else(|payload| Err(Self::panicked(JobError::panicked(payload))));
+
+                    if let Err(err) = result {
+                        if self.handle_error(engine, err.with_entity(&entity)) != ErrorAction::Log {
+                            break;
+                        }
+                    }
+                }
+            }
+            SystemHandle::Parallel(system) => {
+                let errors = Mutex::new(Vec::new());
+
+                let result = engine
+                    .thread_pool()
+                    .par_for_each(&system_entities, |entity| {
+                        let _guard = check_access
+                            .then(|| RunningSystemGuard::new(self.name, &self.signature));
+
+                        if let Err(err) =
+                            event.run_parallel(system.as_ref(), Arc::clone(engine), entity.clone())
+                        {
+                            errors.lock().unwrap().push(err.with_entity(entity));
                         }
                     });
+
+                let mut errors = errors.into_inner().unwrap();
+                if let Err(err) = result {
+                    errors.push(Self::panicked(err));
+                }
+
+                for err in errors {
+                    if self.handle_error(engine, err) != ErrorAction::Log {
+                        break;
+                    }
                 }
             }
-            self.t_pool.borrow().wait();
+        }
+    }
+
+    /// Passes an error from the system to the engine's error handler,
+    /// disabling the system if the handler asks for it
+    fn handle_error(&self, engine: &crate::Engine, mut err: Error) -> ErrorAction {
+        if let Ok(scene) = engine.scenes().get_current_scene_handle() {
+            err = err.with_scene(scene);
         }
 
-        for parallel in parallels.iter() {
-            for system_id in parallel {
-                let (reqs, system) = systems.get(system_id).unwrap();
-
-                let entity_list = engine
-                    .scenes()
-                    .get_current_scene()
-                    .unwrap()
-                    .get_living_entities();
-
-                let system_entities = entity_list
-                    .into_iter()
-                    .filter(|e| {
-                        engine
-                            .scenes()
-                            .get_current_scene()
-                            .unwrap()
-                            .has_components(e, reqs)
-                            .unwrap()
-                    })
-                    .collect::<Vec<_>>();
-
-                let engine_handle = Arc::clone(&engine);
-                let system_handle = Arc::clone(system);
-
-                self.t_pool.borrow_mut().execute(move || {
-                    let mut system = system_handle.lock().unwrap();
-                    for entity in system_entities {
-                        system.on_frame(Arc::clone(&engine_handle), entity.clone(), dt);
-                    }
-                });
+        let action = engine.handle_error(err.with_system(self.name));
+
+        if action == ErrorAction::DisableSystem {
+            self.is_disabled.store(true, Ordering::Relaxed);
+        }
+
+        action
+    }
+
+    /// The error a panic from the system is reported as, caused by the panic message
+    fn panicked(err: JobError) -> Error {
+        Error::from(ErrorKind::SystemPanicked).with_source(err.message())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Engine, Level, RingBufferSink};
+
+    struct Player;
+    struct Enemy;
+
+    /// Stops the engine once it has run a few frames
+    struct StopSystem {
+        frames: u32,
+    }
+
+    impl System for StopSystem {
+        fn on_frame(&mut self, engine: Arc<Engine>, _: Entity, _: Duration) -> Result<(), Error> {
+            self.frames += 1;
+            if self.frames == 3 {
+                engine.stop();
             }
-            self.t_pool.borrow().wait();
+            Ok(())
         }
     }
+
+    struct EnemySystem;
+
+    impl System for EnemySystem {}
+
+    #[test]
+    fn batches_systems_with_disjoint_signatures() {
+        struct Position;
+        struct Velocity;
+        struct Health;
+        struct MoveSystem;
+        impl System for MoveSystem {}
+        struct HealSystem;
+        impl System for HealSystem {}
+        struct DrawSystem;
+        impl System for DrawSystem {}
+        struct ParticleSystem;
+        impl ParallelSystem for ParticleSystem {}
+
+        let (position, velocity, health) = (
+            TypeId::of::<Position>(),
+            TypeId::of::<Velocity>(),
+            TypeId::of::<Health>(),
+        );
+        let systems = SystemManager::new();
+        systems.register_system(&[position, velocity], MoveSystem);
+        systems.register_system(&[health], HealSystem);
+        systems.register_parallel_system(&[health, position], ParticleSystem);
+        systems.register_system(&[], DrawSystem);
+
+        let parallels = systems.systems.system_parallels.lock().unwrap();
+        assert_eq!(
+            *parallels,
+            [
+                vec![
+                    TypeId::of::<MoveSystem>(),
+                    TypeId::of::<HealSystem>(),
+                    TypeId::of::<DrawSystem>()
+                ],
+                vec![TypeId::of::<ParticleSystem>()],
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_signatures_with_unregistered_components() {
+        let mut engine = Engine::new();
+        let (scene, other) = (
+            engine.create_scene().unwrap(),
+            engine.create_scene().unwrap(),
+        );
+        let state = engine.scenes().get_scene(&scene).unwrap();
+        let player = state.register_component::<Player>();
+        let enemy = engine
+            .scenes()
+            .get_scene(&other)
+            .unwrap()
+            .register_component::<Enemy>();
+
+        let err = state
+            .register_system(&[player, enemy], EnemySystem)
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::ComponentNotRegistered));
+        assert_eq!(err.system(), Some(type_name::<EnemySystem>()));
+        assert_eq!(err.component(), Some(type_name::<Enemy>()));
+
+        let err = state
+            .register_system(&[TypeId::of::<StopSystem>()], EnemySystem)
+            .unwrap_err();
+        assert_eq!(err.component(), Some("unknown type"));
+
+        state.register_component::<Enemy>();
+        assert!(state.register_system(&[player, enemy], EnemySystem).is_ok());
+    }
+
+    #[test]
+    fn systems_matching_nothing_yet_run_quietly() {
+        let mut engine = Engine::new();
+        let sink = Arc::new(RingBufferSink::new(64));
+        engine.logger().add_sink(sink.clone());
+
+        let scene = engine.create_scene().unwrap();
+        let state = engine.scenes().get_scene(&scene).unwrap();
+        let player = state.register_component::<Player>();
+        let enemy = state.register_component::<Enemy>();
+
+        let entity = state.create_entity().unwrap();
+        state.add_component(&entity, Player).unwrap();
+        state
+            .register_system(&[player], StopSystem { frames: 0 })
+            .unwrap();
+        // enemies are spawned later in a real game, so this isn't worth a warning
+        state.register_system(&[enemy], EnemySystem).unwrap();
+
+        engine.run(&scene).unwrap();
+
+        let records = sink.records();
+        assert!(!records.iter().any(|record| record.level <= Level::Warn));
+    }
+
+    /// Fails every frame, counting how often it ran
+    struct FailingSystem {
+        runs: Arc<std::sync::atomic::AtomicU32>,
+    }
+
+    impl System for FailingSystem {
+        fn on_frame(&mut self, _: Arc<Engine>, _: Entity, _: Duration) -> Result<(), Error> {
+            self.runs.fetch_add(1, Ordering::Relaxed);
+            Err(Error::from(ErrorKind::Other).with_component("Player"))
+        }
+    }
+
+    /// Runs a scene with a failing system until StopSystem stops it or the handler aborts,
+    /// returning how often the failing system ran and what run() returned
+    fn run_failing_system(action: ErrorAction) -> (u32, Result<(), Error>) {
+        let mut engine = Engine::new();
+        engine.set_error_handler(move |err| {
+            assert_eq!(err.system(), Some(type_name::<FailingSystem>()));
+            action
+        });
+
+        let scene = engine.create_scene().unwrap();
+        let state = engine.scenes().get_scene(&scene).unwrap();
+        let player = state.register_component::<Player>();
+        let entity = state.create_entity().unwrap();
+        state.add_component(&entity, Player).unwrap();
+
+        let runs = Arc::default();
+        let failing = FailingSystem {
+            runs: Arc::clone(&runs),
+        };
+        state.register_system(&[player], failing).unwrap();
+        state
+            .register_system(&[player], StopSystem { frames: 0 })
+            .unwrap();
+
+        let result = engine.run(&scene);
+        (runs.load(Ordering::Relaxed), result)
+    }
+
+    #[test]
+    fn logged_errors_keep_the_system_running() {
+        let (runs, result) = run_failing_system(ErrorAction::Log);
+        assert_eq!(runs, 3);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn disabled_systems_stop_running() {
+        let (runs, result) = run_failing_system(ErrorAction::DisableSystem);
+        assert_eq!(runs, 1);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn aborting_returns_the_error_from_run() {
+        let (runs, result) = run_failing_system(ErrorAction::Abort);
+        assert_eq!(runs, 1);
+
+        let err = result.unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Other));
+        assert_eq!(err.system(), Some(type_name::<FailingSystem>()));
+        assert_eq!(err.component(), Some("Player"));
+    }
 }
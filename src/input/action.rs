@@ -0,0 +1,216 @@
+use std::{collections::HashMap, fmt, fs, path::Path, str::FromStr};
+
+use super::{Error, ErrorKind, GamepadButton, Key, MouseButton};
+
+/// A button that can trigger an action
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Key(Key),
+    Mouse(MouseButton),
+    /// A button on any gamepad
+    Gamepad(GamepadButton),
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{key}"),
+            Binding::Mouse(button) => write!(f, "mouse.{button}"),
+            Binding::Gamepad(button) => write!(f, "gamepad.{button}"),
+        }
+    }
+}
+
+impl FromStr for Binding {
+    type Err = ();
+
+    /// Parses a key name such as `space`, or a button prefixed with `mouse.` or `gamepad.`
+    ///
+    /// Gamepad face buttons can also be written with their Xbox names, `a`, `b`, `x` and `y`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_ascii_lowercase();
+
+        if let Some(button) = s.strip_prefix("mouse.") {
+            return button.parse().map(Binding::Mouse);
+        }
+
+        if let Some(button) = s.strip_prefix("gamepad.") {
+            let button = match button {
+                "a" => GamepadButton::South,
+                "b" => GamepadButton::East,
+                "x" => GamepadButton::West,
+                "y" => GamepadButton::North,
+                _ => button.parse()?,
+            };
+            return Ok(Binding::Gamepad(button));
+        }
+
+        s.parse().map(Binding::Key)
+    }
+}
+
+/// Named actions and the buttons bound to them
+///
+/// Binding files are made of `action = binding, binding` lines, lines starting with `#` are comments
+///
+/// ```text
+/// # keys are written by name
+/// jump = space, gamepad.a
+/// # mouse and gamepad buttons are prefixed
+/// shoot = mouse.left, gamepad.right_trigger
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ActionMap {
+    actions: HashMap<String, Vec<Binding>>,
+}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads action bindings from a file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// Binds a button to an action, an action can have any number of bindings
+    pub fn bind(&mut self, action: &str, binding: Binding) {
+        let bindings = self.actions.entry(action.to_string()).or_default();
+
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    /// Removes every binding of an action
+    pub fn unbind(&mut self, action: &str) {
+        self.actions.remove(action);
+    }
+
+    /// The buttons bound to an action, empty if the action doesn't exist
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The names of every action with bindings
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.actions.keys().map(String::as_str)
+    }
+}
+
+impl FromStr for ActionMap {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut action_map = Self::new();
+
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (action, bindings) = line
+                .split_once('=')
+                .ok_or(Error::from(ErrorKind::MalformedLine(index + 1)))?;
+            let action = action.trim();
+
+            if action.is_empty() {
+                return Err(ErrorKind::MalformedLine(index + 1).into());
+            }
+
+            for binding in bindings.split(',').map(str::trim) {
+                let parsed = binding.parse().map_err(|_| ErrorKind::UnknownBinding {
+                    action: action.to_string(),
+                    binding: binding.to_string(),
+                })?;
+
+                action_map.bind(action, parsed);
+            }
+        }
+
+        Ok(action_map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bindings() {
+        assert_eq!("space".parse(), Ok(Binding::Key(Key::Space)));
+        assert_eq!("Escape".parse(), Ok(Binding::Key(Key::Escape)));
+        assert_eq!("mouse.left".parse(), Ok(Binding::Mouse(MouseButton::Left)));
+        assert_eq!(
+            "gamepad.a".parse(),
+            Ok(Binding::Gamepad(GamepadButton::South))
+        );
+        assert_eq!(
+            "gamepad.right_trigger".parse(),
+            Ok(Binding::Gamepad(GamepadButton::RightTrigger))
+        );
+        assert_eq!("mouse.space".parse::<Binding>(), Err(()));
+        assert_eq!("gamepad.".parse::<Binding>(), Err(()));
+    }
+
+    #[test]
+    fn bindings_round_trip_through_their_names() {
+        let bindings = [
+            Binding::Key(Key::Up),
+            Binding::Mouse(MouseButton::Left),
+            Binding::Gamepad(GamepadButton::North),
+        ];
+
+        for binding in bindings {
+            assert_eq!(binding.to_string().parse(), Ok(binding));
+        }
+    }
+
+    #[test]
+    fn parses_binding_files() {
+        let action_map = "
+            # comments and blank lines are skipped
+
+            jump = space, gamepad.a
+            shoot=mouse.left
+            jump = space, up
+        "
+        .parse::<ActionMap>()
+        .unwrap();
+
+        assert_eq!(
+            action_map.bindings("jump"),
+            [
+                Binding::Key(Key::Space),
+                Binding::Gamepad(GamepadButton::South),
+                Binding::Key(Key::Up),
+            ]
+        );
+        assert_eq!(
+            action_map.bindings("shoot"),
+            [Binding::Mouse(MouseButton::Left)]
+        );
+        assert!(action_map.bindings("missing").is_empty());
+        assert_eq!(action_map.actions().count(), 2);
+    }
+
+    #[test]
+    fn reports_the_line_of_malformed_bindings() {
+        let err = "jump = space\nshoot".parse::<ActionMap>().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::MalformedLine(2)));
+
+        let err = "= space".parse::<ActionMap>().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::MalformedLine(1)));
+
+        let err = "jump = space, hover".parse::<ActionMap>().unwrap_err();
+        match err.kind() {
+            ErrorKind::UnknownBinding { action, binding } => {
+                assert_eq!((action.as_str(), binding.as_str()), ("jump", "hover"));
+            }
+            _ => panic!("unexpected error: {err}"),
+        }
+    }
+}
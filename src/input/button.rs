@@ -0,0 +1,152 @@
+use std::{fmt, str::FromStr};
+
+/// Defines an enum of buttons along with the names they are written as in binding files
+macro_rules! named_buttons {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident => $text:literal,)* }) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant,)*
+        }
+
+        impl $name {
+            /// The name of the button in binding files
+            pub fn as_str(&self) -> &'static str {
+                match *self {
+                    $($name::$variant => $text,)*
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.as_str())
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ();
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s.to_ascii_lowercase().as_str() {
+                    $($text => Ok($name::$variant),)*
+                    _ => Err(()),
+                }
+            }
+        }
+    };
+}
+
+named_buttons! {
+    /// A keyboard key
+    Key {
+        A => "a",
+        B => "b",
+        C => "c",
+        D => "d",
+        E => "e",
+        F => "f",
+        G => "g",
+        H => "h",
+        I => "i",
+        J => "j",
+        K => "k",
+        L => "l",
+        M => "m",
+        N => "n",
+        O => "o",
+        P => "p",
+        Q => "q",
+        R => "r",
+        S => "s",
+        T => "t",
+        U => "u",
+        V => "v",
+        W => "w",
+        X => "x",
+        Y => "y",
+        Z => "z",
+        Num0 => "0",
+        Num1 => "1",
+        Num2 => "2",
+        Num3 => "3",
+        Num4 => "4",
+        Num5 => "5",
+        Num6 => "6",
+        Num7 => "7",
+        Num8 => "8",
+        Num9 => "9",
+        F1 => "f1",
+        F2 => "f2",
+        F3 => "f3",
+        F4 => "f4",
+        F5 => "f5",
+        F6 => "f6",
+        F7 => "f7",
+        F8 => "f8",
+        F9 => "f9",
+        F10 => "f10",
+        F11 => "f11",
+        F12 => "f12",
+        Up => "up",
+        Down => "down",
+        Left => "left",
+        Right => "right",
+        Space => "space",
+        Enter => "enter",
+        Escape => "escape",
+        Tab => "tab",
+        Backspace => "backspace",
+        LeftShift => "left_shift",
+        RightShift => "right_shift",
+        LeftControl => "left_control",
+        RightControl => "right_control",
+        LeftAlt => "left_alt",
+        RightAlt => "right_alt",
+    }
+}
+
+named_buttons! {
+    /// A mouse button
+    MouseButton {
+        Left => "left",
+        Right => "right",
+        Middle => "middle",
+        Back => "back",
+        Forward => "forward",
+    }
+}
+
+named_buttons! {
+    /// A gamepad button, named by its position so it means the same thing on every gamepad
+    GamepadButton {
+        South => "south",
+        East => "east",
+        West => "west",
+        North => "north",
+        LeftBumper => "left_bumper",
+        RightBumper => "right_bumper",
+        LeftTrigger => "left_trigger",
+        RightTrigger => "right_trigger",
+        Select => "select",
+        Start => "start",
+        LeftStick => "left_stick",
+        RightStick => "right_stick",
+        DPadUp => "dpad_up",
+        DPadDown => "dpad_down",
+        DPadLeft => "dpad_left",
+        DPadRight => "dpad_right",
+    }
+}
+
+named_buttons! {
+    /// A gamepad stick or trigger axis
+    GamepadAxis {
+        LeftStickX => "left_stick_x",
+        LeftStickY => "left_stick_y",
+        RightStickX => "right_stick_x",
+        RightStickY => "right_stick_y",
+        LeftTrigger => "left_trigger",
+        RightTrigger => "right_trigger",
+    }
+}
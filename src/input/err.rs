@@ -0,0 +1,76 @@
+use std::{error, fmt, io};
+
+/// Input Error
+pub struct Error {
+    kind: ErrorKind,
+}
+
+impl Error {
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Input Error: {}!", self.kind)
+    }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Input Error: {}!", self.kind)
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(value: ErrorKind) -> Self {
+        Self { kind: value }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        ErrorKind::Io(value).into()
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Types of Input Errors
+pub enum ErrorKind {
+    /// A bindings or recording file could not be read
+    Io(io::Error),
+    /// A line that isn't a comment or an `action = bindings` pair, holds the line number
+    MalformedLine(usize),
+    UnknownBinding {
+        action: String,
+        binding: String,
+    },
+    /// A recording line that isn't a frame or an event, holds the line number
+    MalformedRecording(usize),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::Io(err) => write!(f, "failed to read input file ({err})"),
+            ErrorKind::MalformedLine(line) => {
+                write!(f, "line {line} is not an `action = bindings` pair")
+            }
+            ErrorKind::UnknownBinding { action, binding } => {
+                write!(f, "unknown binding `{binding}` for action `{action}`")
+            }
+            ErrorKind::MalformedRecording(line) => {
+                write!(f, "line {line} of the recording is not a frame or an event")
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::{fmt, str::FromStr};
+
+use super::{GamepadAxis, GamepadButton, Key, MouseButton};
+
+/// A single change to the input state, reported by an input source
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputEvent {
+    KeyPressed(Key),
+    KeyReleased(Key),
+    MouseButtonPressed(MouseButton),
+    MouseButtonReleased(MouseButton),
+    /// The mouse moved to a position in window coordinates
+    MouseMoved {
+        x: f32,
+        y: f32,
+    },
+    MouseScrolled {
+        dx: f32,
+        dy: f32,
+    },
+    GamepadButtonPressed {
+        gamepad: usize,
+        button: GamepadButton,
+    },
+    GamepadButtonReleased {
+        gamepad: usize,
+        button: GamepadButton,
+    },
+    /// A gamepad axis moved to a value, -1 to 1 for sticks and 0 to 1 for triggers
+    GamepadAxisMoved {
+        gamepad: usize,
+        axis: GamepadAxis,
+        value: f32,
+    },
+}
+
+impl fmt::Display for InputEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputEvent::KeyPressed(key) => write!(f, "key_pressed {key}"),
+            InputEvent::KeyReleased(key) => write!(f, "key_released {key}"),
+            InputEvent::MouseButtonPressed(button) => write!(f, "mouse_pressed {button}"),
+            InputEvent::MouseButtonReleased(button) => write!(f, "mouse_released {button}"),
+            InputEvent::MouseMoved { x, y } => write!(f, "mouse_moved {x} {y}"),
+            InputEvent::MouseScrolled { dx, dy } => write!(f, "mouse_scrolled {dx} {dy}"),
+            InputEvent::GamepadButtonPressed { gamepad, button } => {
+                write!(f, "gamepad_pressed {gamepad} {button}")
+            }
+            InputEvent::GamepadButtonReleased { gamepad, button } => {
+                write!(f, "gamepad_released {gamepad} {button}")
+            }
+            InputEvent::GamepadAxisMoved {
+                gamepad,
+                axis,
+                value,
+            } => write!(f, "gamepad_axis {gamepad} {axis} {value}"),
+        }
+    }
+}
+
+impl FromStr for InputEvent {
+    type Err = ();
+
+    /// Parses an event in the format it is displayed in, such as `key_pressed space`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let name = parts.next().ok_or(())?;
+        let mut next = || parts.next().ok_or(());
+
+        let event = match name {
+            "key_pressed" => InputEvent::KeyPressed(next()?.parse()?),
+            "key_released" => InputEvent::KeyReleased(next()?.parse()?),
+            "mouse_pressed" => InputEvent::MouseButtonPressed(next()?.parse()?),
+            "mouse_released" => InputEvent::MouseButtonReleased(next()?.parse()?),
+            "mouse_moved" => InputEvent::MouseMoved {
+                x: next()?.parse().map_err(|_| ())?,
+                y: next()?.parse().map_err(|_| ())?,
+            },
+            "mouse_scrolled" => InputEvent::MouseScrolled {
+                dx: next()?.parse().map_err(|_| ())?,
+                dy: next()?.parse().map_err(|_| ())?,
+            },
+            "gamepad_pressed" => InputEvent::GamepadButtonPressed {
+                gamepad: next()?.parse().map_err(|_| ())?,
+                button: next()?.parse()?,
+            },
+            "gamepad_released" => InputEvent::GamepadButtonReleased {
+                gamepad: next()?.parse().map_err(|_| ())?,
+                button: next()?.parse()?,
+            },
+            "gamepad_axis" => InputEvent::GamepadAxisMoved {
+                gamepad: next()?.parse().map_err(|_| ())?,
+                axis: next()?.parse()?,
+                value: next()?.parse().map_err(|_| ())?,
+            },
+            _ => return Err(()),
+        };
+
+        // trailing parts mean the line isn't an event
+        match parts.next() {
+            Some(_) => Err(()),
+            None => Ok(event),
+        }
+    }
+}
@@ -0,0 +1,300 @@
+mod action;
+mod button;
+mod err;
+mod event;
+mod record;
+mod source;
+mod terminal;
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    sync::RwLock,
+};
+
+pub use action::{ActionMap, Binding};
+pub use button::{GamepadAxis, GamepadButton, Key, MouseButton};
+pub use err::{Error, ErrorKind};
+pub use event::InputEvent;
+pub(crate) use record::InputRecorder;
+pub use record::{InputRecording, RecordedFrame};
+pub use source::{InputSource, ScriptedInput};
+pub(crate) use terminal::stty;
+pub use terminal::TerminalInput;
+
+/// Which buttons of one type are held, and which changed this frame
+struct ButtonState<T> {
+    pressed: HashSet<T>,
+    just_pressed: HashSet<T>,
+    just_released: HashSet<T>,
+}
+
+impl<T: Copy + Eq + Hash> ButtonState<T> {
+    fn new() -> Self {
+        Self {
+            pressed: HashSet::new(),
+            just_pressed: HashSet::new(),
+            just_released: HashSet::new(),
+        }
+    }
+
+    fn press(&mut self, button: T) {
+        // repeated presses from held keys aren't new presses
+        if self.pressed.insert(button) {
+            self.just_pressed.insert(button);
+        }
+    }
+
+    fn release(&mut self, button: T) {
+        if self.pressed.remove(&button) {
+            self.just_released.insert(button);
+        }
+    }
+
+    fn end_frame(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+    }
+}
+
+struct InputState {
+    keys: ButtonState<Key>,
+    mouse_buttons: ButtonState<MouseButton>,
+    gamepad_buttons: ButtonState<(usize, GamepadButton)>,
+    gamepad_axes: HashMap<(usize, GamepadAxis), f32>,
+    mouse_position: (f32, f32),
+    mouse_delta: (f32, f32),
+    scroll_delta: (f32, f32),
+    action_map: ActionMap,
+}
+
+impl InputState {
+    fn apply(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::KeyPressed(key) => self.keys.press(key),
+            InputEvent::KeyReleased(key) => self.keys.release(key),
+            InputEvent::MouseButtonPressed(button) => self.mouse_buttons.press(button),
+            InputEvent::MouseButtonReleased(button) => self.mouse_buttons.release(button),
+            InputEvent::MouseMoved { x, y } => {
+                self.mouse_delta.0 += x - self.mouse_position.0;
+                self.mouse_delta.1 += y - self.mouse_position.1;
+                self.mouse_position = (x, y);
+            }
+            InputEvent::MouseScrolled { dx, dy } => {
+                self.scroll_delta.0 += dx;
+                self.scroll_delta.1 += dy;
+            }
+            InputEvent::GamepadButtonPressed { gamepad, button } => {
+                self.gamepad_buttons.press((gamepad, button));
+            }
+            InputEvent::GamepadButtonReleased { gamepad, button } => {
+                self.gamepad_buttons.release((gamepad, button));
+            }
+            InputEvent::GamepadAxisMoved {
+                gamepad,
+                axis,
+                value,
+            } => {
+                self.gamepad_axes.insert((gamepad, axis), value);
+            }
+        }
+    }
+}
+
+/// The keyboard, mouse and gamepad state for the current frame
+///
+/// The engine updates the input from its input source at the start of every
+/// frame, so every system sees the same state for the whole frame. Buttons are
+/// just pressed or just released on the frame their event was polled
+pub struct Input {
+    state: RwLock<InputState>,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self {
+            state: RwLock::new(InputState {
+                keys: ButtonState::new(),
+                mouse_buttons: ButtonState::new(),
+                gamepad_buttons: ButtonState::new(),
+                gamepad_axes: HashMap::new(),
+                mouse_position: (0.0, 0.0),
+                mouse_delta: (0.0, 0.0),
+                scroll_delta: (0.0, 0.0),
+                action_map: ActionMap::new(),
+            }),
+        }
+    }
+
+    /// Starts a new frame, applying the events that happened since the last frame
+    pub(crate) fn update(&self, events: &[InputEvent]) {
+        let mut state = self.state.write().unwrap();
+
+        state.keys.end_frame();
+        state.mouse_buttons.end_frame();
+        state.gamepad_buttons.end_frame();
+        state.mouse_delta = (0.0, 0.0);
+        state.scroll_delta = (0.0, 0.0);
+
+        for event in events {
+            state.apply(*event);
+        }
+    }
+
+    pub fn is_key_pressed(&self, key: Key) -> bool {
+        self.state.read().unwrap().keys.pressed.contains(&key)
+    }
+
+    pub fn is_key_just_pressed(&self, key: Key) -> bool {
+        self.state.read().unwrap().keys.just_pressed.contains(&key)
+    }
+
+    pub fn is_key_just_released(&self, key: Key) -> bool {
+        self.state.read().unwrap().keys.just_released.contains(&key)
+    }
+
+    pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.state
+            .read()
+            .unwrap()
+            .mouse_buttons
+            .pressed
+            .contains(&button)
+    }
+
+    pub fn is_mouse_button_just_pressed(&self, button: MouseButton) -> bool {
+        self.state
+            .read()
+            .unwrap()
+            .mouse_buttons
+            .just_pressed
+            .contains(&button)
+    }
+
+    pub fn is_mouse_button_just_released(&self, button: MouseButton) -> bool {
+        self.state
+            .read()
+            .unwrap()
+            .mouse_buttons
+            .just_released
+            .contains(&button)
+    }
+
+    /// The mouse position in window coordinates
+    pub fn mouse_position(&self) -> (f32, f32) {
+        self.state.read().unwrap().mouse_position
+    }
+
+    /// How far the mouse moved this frame
+    pub fn mouse_delta(&self) -> (f32, f32) {
+        self.state.read().unwrap().mouse_delta
+    }
+
+    /// How far the mouse wheel scrolled this frame
+    pub fn scroll_delta(&self) -> (f32, f32) {
+        self.state.read().unwrap().scroll_delta
+    }
+
+    pub fn is_gamepad_button_pressed(&self, gamepad: usize, button: GamepadButton) -> bool {
+        self.state
+            .read()
+            .unwrap()
+            .gamepad_buttons
+            .pressed
+            .contains(&(gamepad, button))
+    }
+
+    pub fn is_gamepad_button_just_pressed(&self, gamepad: usize, button: GamepadButton) -> bool {
+        self.state
+            .read()
+            .unwrap()
+            .gamepad_buttons
+            .just_pressed
+            .contains(&(gamepad, button))
+    }
+
+    pub fn is_gamepad_button_just_released(&self, gamepad: usize, button: GamepadButton) -> bool {
+        self.state
+            .read()
+            .unwrap()
+            .gamepad_buttons
+            .just_released
+            .contains(&(gamepad, button))
+    }
+
+    /// The value of a gamepad axis, 0 if the axis hasn't moved
+    pub fn gamepad_axis(&self, gamepad: usize, axis: GamepadAxis) -> f32 {
+        self.state
+            .read()
+            .unwrap()
+            .gamepad_axes
+            .get(&(gamepad, axis))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Replaces the action bindings
+    pub fn set_action_map(&self, action_map: ActionMap) {
+        self.state.write().unwrap().action_map = action_map;
+    }
+
+    /// A copy of the current action bindings
+    pub fn action_map(&self) -> ActionMap {
+        self.state.read().unwrap().action_map.clone()
+    }
+
+    /// Checks if any button bound to the action is held
+    pub fn is_action_pressed(&self, action: &str) -> bool {
+        self.any_binding(action, |state, binding| match binding {
+            Binding::Key(key) => state.keys.pressed.contains(&key),
+            Binding::Mouse(button) => state.mouse_buttons.pressed.contains(&button),
+            Binding::Gamepad(button) => state
+                .gamepad_buttons
+                .pressed
+                .iter()
+                .any(|(_, pressed)| *pressed == button),
+        })
+    }
+
+    /// Checks if a button bound to the action was pressed this frame
+    pub fn is_action_just_pressed(&self, action: &str) -> bool {
+        self.any_binding(action, |state, binding| match binding {
+            Binding::Key(key) => state.keys.just_pressed.contains(&key),
+            Binding::Mouse(button) => state.mouse_buttons.just_pressed.contains(&button),
+            Binding::Gamepad(button) => state
+                .gamepad_buttons
+                .just_pressed
+                .iter()
+                .any(|(_, pressed)| *pressed == button),
+        })
+    }
+
+    /// Checks if a button bound to the action was released this frame
+    pub fn is_action_just_released(&self, action: &str) -> bool {
+        self.any_binding(action, |state, binding| match binding {
+            Binding::Key(key) => state.keys.just_released.contains(&key),
+            Binding::Mouse(button) => state.mouse_buttons.just_released.contains(&button),
+            Binding::Gamepad(button) => state
+                .gamepad_buttons
+                .just_released
+                .iter()
+                .any(|(_, released)| *released == button),
+        })
+    }
+
+    fn any_binding(&self, action: &str, f: impl Fn(&InputState, Binding) -> bool) -> bool {
+        let state = self.state.read().unwrap();
+
+        state
+            .action_map
+            .bindings(action)
+            .iter()
+            .any(|binding| f(&state, *binding))
+    }
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,293 @@
+use std::{
+    fmt, fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+    str::FromStr,
+    time::Duration,
+};
+
+use super::{Error, ErrorKind, InputEvent};
+
+/// Everything a frame of the simulation depends on from outside the engine
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedFrame {
+    pub dt: Duration,
+    pub physics_steps: u32,
+    pub events: Vec<InputEvent>,
+}
+
+/// The frames of a recorded run, replaying them reproduces the run
+///
+/// Recording files have a `frame <dt in nanoseconds> <physics steps>` line for every
+/// frame, followed by a line for each input event of that frame
+///
+/// ```text
+/// frame 16666667 1
+/// key_pressed space
+/// mouse_moved 120 48.5
+/// frame 16702114 1
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InputRecording {
+    frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    pub fn new(frames: Vec<RecordedFrame>) -> Self {
+        Self { frames }
+    }
+
+    /// Loads a recording from a file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    pub fn frames(&self) -> &[RecordedFrame] {
+        &self.frames
+    }
+
+    pub(crate) fn into_frames(self) -> Vec<RecordedFrame> {
+        self.frames
+    }
+}
+
+impl FromStr for InputRecording {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut frames: Vec<RecordedFrame> = Vec::new();
+
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let malformed_line = || Error::from(ErrorKind::MalformedRecording(index + 1));
+
+            if let Some(frame) = line.strip_prefix("frame ") {
+                let (dt, physics_steps) = frame.split_once(' ').ok_or_else(malformed_line)?;
+
+                frames.push(RecordedFrame {
+                    dt: Duration::from_nanos(dt.parse().map_err(|_| malformed_line())?),
+                    physics_steps: physics_steps.parse().map_err(|_| malformed_line())?,
+                    events: Vec::new(),
+                });
+            } else {
+                // events have to belong to a frame
+                let frame = frames.last_mut().ok_or_else(malformed_line)?;
+                frame
+                    .events
+                    .push(line.parse().map_err(|_| malformed_line())?);
+            }
+        }
+
+        Ok(Self { frames })
+    }
+}
+
+impl fmt::Display for RecordedFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "frame {} {}", self.dt.as_nanos(), self.physics_steps)?;
+
+        for event in self.events.iter() {
+            writeln!(f, "{event}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for InputRecording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.frames
+            .iter()
+            .try_for_each(|frame| write!(f, "{frame}"))
+    }
+}
+
+/// Writes frames to a recording file as they happen, so a crash keeps every frame before it
+pub(crate) struct InputRecorder {
+    writer: BufWriter<fs::File>,
+}
+
+impl InputRecorder {
+    /// Creates the recording file, replacing it if it already exists
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(fs::File::create(path)?),
+        })
+    }
+
+    pub fn write_frame(&mut self, frame: &RecordedFrame) -> io::Result<()> {
+        write!(self.writer, "{frame}")?;
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{ecs, Engine, Entity, Key, MouseButton, System};
+
+    const RECORDING: &str = "
+        # a recorded run
+        frame 16666667 1
+        key_pressed space
+        mouse_moved 120 48.5
+        frame 33000000 2
+
+        frame 16000000 0
+        key_released space
+        mouse_pressed left
+    ";
+
+    #[test]
+    fn parses_recordings() {
+        let recording = RECORDING.parse::<InputRecording>().unwrap();
+
+        let frames = recording.frames();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].dt, Duration::from_nanos(16_666_667));
+        assert_eq!(frames[1].physics_steps, 2);
+        assert_eq!(
+            frames[0].events,
+            [
+                InputEvent::KeyPressed(Key::Space),
+                InputEvent::MouseMoved { x: 120.0, y: 48.5 },
+            ]
+        );
+        assert!(frames[1].events.is_empty());
+        assert_eq!(
+            frames[2].events,
+            [
+                InputEvent::KeyReleased(Key::Space),
+                InputEvent::MouseButtonPressed(MouseButton::Left),
+            ]
+        );
+    }
+
+    #[test]
+    fn recordings_round_trip_through_their_text() {
+        let recording = RECORDING.parse::<InputRecording>().unwrap();
+        assert_eq!(
+            recording.to_string().parse::<InputRecording>().unwrap(),
+            recording
+        );
+    }
+
+    #[test]
+    fn reports_the_line_of_malformed_recordings() {
+        let malformed = [
+            ("key_pressed space\nframe 1 1", 1),
+            ("frame 1 1\nframe 1", 2),
+            ("frame 1 -1", 1),
+            ("frame 1.5 1", 1),
+            ("frame 1 1\n\nkey_pressed hover", 3),
+        ];
+
+        for (recording, line) in malformed {
+            let err = recording.parse::<InputRecording>().unwrap_err();
+            assert!(
+                matches!(err.kind(), ErrorKind::MalformedRecording(l) if *l == line),
+                "{recording:?}: {err}"
+            );
+        }
+    }
+
+    struct Player;
+
+    /// What a frame of the simulation saw
+    #[derive(Clone, Debug, PartialEq)]
+    struct Observation {
+        dt: Duration,
+        physics_frames: u32,
+        is_space_pressed: bool,
+        mouse_position: (f32, f32),
+    }
+
+    struct ObserveSystem {
+        physics_frames: u32,
+        observations: Arc<Mutex<Vec<Observation>>>,
+    }
+
+    impl System for ObserveSystem {
+        fn on_physics_frame(&mut self, _: Arc<Engine>, _: Entity) -> Result<(), ecs::Error> {
+            self.physics_frames += 1;
+            Ok(())
+        }
+
+        fn on_frame(
+            &mut self,
+            engine: Arc<Engine>,
+            _: Entity,
+            dt: Duration,
+        ) -> Result<(), ecs::Error> {
+            self.observations.lock().unwrap().push(Observation {
+                dt,
+                physics_frames: self.physics_frames,
+                is_space_pressed: engine.input().is_key_pressed(Key::Space),
+                mouse_position: engine.input().mouse_position(),
+            });
+            Ok(())
+        }
+    }
+
+    fn replay(recording: &InputRecording) -> Vec<Observation> {
+        let observations = Arc::new(Mutex::new(Vec::new()));
+
+        let mut engine = Engine::new();
+        engine.replay_input(recording.clone());
+        assert!(engine.is_deterministic());
+
+        let scene = engine.create_scene().unwrap();
+        let state = engine.scenes().get_scene(&scene).unwrap();
+        let player = state.register_component::<Player>();
+        let entity = state.create_entity().unwrap();
+        state.add_component(&entity, Player).unwrap();
+        let system = ObserveSystem {
+            physics_frames: 0,
+            observations: Arc::clone(&observations),
+        };
+        state.register_system(&[player], system).unwrap();
+
+        engine.run(&scene).unwrap();
+
+        let observations = observations.lock().unwrap();
+        observations.clone()
+    }
+
+    #[test]
+    fn replays_are_deterministic() {
+        let recording = RECORDING.parse::<InputRecording>().unwrap();
+
+        let observations = replay(&recording);
+        assert_eq!(
+            observations,
+            [
+                Observation {
+                    dt: Duration::from_nanos(16_666_667),
+                    physics_frames: 1,
+                    is_space_pressed: true,
+                    mouse_position: (120.0, 48.5),
+                },
+                Observation {
+                    dt: Duration::from_nanos(33_000_000),
+                    physics_frames: 3,
+                    is_space_pressed: true,
+                    mouse_position: (120.0, 48.5),
+                },
+                Observation {
+                    dt: Duration::from_nanos(16_000_000),
+                    physics_frames: 3,
+                    is_space_pressed: false,
+                    mouse_position: (120.0, 48.5),
+                },
+            ]
+        );
+        assert_eq!(replay(&recording), observations);
+    }
+}
@@ -0,0 +1,68 @@
+use super::InputEvent;
+
+/// Where the engine gets its input from
+///
+/// The engine polls its input source once at the start of every frame, a window
+/// backend reports the events of its window while tests and headless runs can
+/// use a ScriptedInput instead
+pub trait InputSource: Send {
+    /// Adds every event that happened since the last poll to events, in the order they happened
+    fn poll(&mut self, events: &mut Vec<InputEvent>);
+}
+
+/// An input source that reports a list of events on the frames they were scripted for
+///
+/// Frames are counted from the first poll, which is frame 0. Resetting the
+/// script replays it from the start
+#[derive(Clone, Debug, Default)]
+pub struct ScriptedInput {
+    /// scripted events, sorted by frame
+    events: Vec<(u64, InputEvent)>,
+    /// the next frame to be polled
+    frame: u64,
+}
+
+impl ScriptedInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an event that is reported on the given frame
+    ///
+    /// Events scripted for the same frame are reported in the order they were added
+    pub fn at_frame(mut self, frame: u64, event: InputEvent) -> Self {
+        self.push(frame, event);
+        self
+    }
+
+    /// Adds an event that is reported on the given frame
+    pub fn push(&mut self, frame: u64, event: InputEvent) {
+        let index = self.events.partition_point(|(f, _)| *f <= frame);
+        self.events.insert(index, (frame, event));
+    }
+
+    /// The next frame that will be polled
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Checks if every scripted event has been reported
+    pub fn is_finished(&self) -> bool {
+        self.events.last().is_none_or(|(f, _)| *f < self.frame)
+    }
+
+    /// Starts the script over from frame 0
+    pub fn reset(&mut self) {
+        self.frame = 0;
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn poll(&mut self, events: &mut Vec<InputEvent>) {
+        let start = self.events.partition_point(|(f, _)| *f < self.frame);
+        let end = self.events.partition_point(|(f, _)| *f <= self.frame);
+
+        events.extend(self.events[start..end].iter().map(|(_, event)| *event));
+        self.frame += 1;
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    io::{self, Read},
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use super::{InputEvent, InputSource, Key};
+
+/// Reads key presses from the terminal the engine runs in
+///
+/// The terminal is put in raw mode with `stty` so keys are read as soon as they are
+/// pressed and aren't echoed, it is restored when the input source is dropped.
+/// Terminals don't report key releases, so a key is released on the first poll that
+/// doesn't read it again, held keys stay pressed as long as the terminal repeats them
+/// faster than the engine polls. Ctrl+C is reported as left control and c instead of
+/// stopping the program
+pub struct TerminalInput {
+    bytes: Receiver<Vec<u8>>,
+    /// the start of an escape sequence split across reads, parsed once the rest arrives
+    unparsed: Vec<u8>,
+    /// keys read on the last poll, released once a poll doesn't read them again
+    pressed: Vec<Key>,
+    /// the terminal settings from before raw mode
+    saved_mode: String,
+}
+
+impl TerminalInput {
+    /// Puts the terminal in raw mode and starts reading stdin on a background thread
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if stdin isn't a terminal or `stty` failed
+    pub fn new() -> io::Result<Self> {
+        let saved_mode = stty(&["-g"])?;
+        stty(&["raw", "-echo"])?;
+
+        let (sender, bytes) = mpsc::channel();
+        thread::Builder::new()
+            .name("terminal input".to_owned())
+            .spawn(move || {
+                let mut stdin = io::stdin();
+                let mut buffer = [0; 64];
+
+                // stops once stdin closes, or on the next read after the input source is dropped
+                while let Ok(count @ 1..) = stdin.read(&mut buffer) {
+                    if sender.send(buffer[..count].to_vec()).is_err() {
+                        break;
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            bytes,
+            unparsed: Vec::new(),
+            pressed: Vec::new(),
+            saved_mode: saved_mode.trim().to_owned(),
+        })
+    }
+}
+
+impl InputSource for TerminalInput {
+    fn poll(&mut self, events: &mut Vec<InputEvent>) {
+        while let Ok(bytes) = self.bytes.try_recv() {
+            self.unparsed.extend(bytes);
+        }
+
+        let mut keys = Vec::new();
+        let parsed = parse_keys(&self.unparsed, &mut keys);
+        self.unparsed.drain(..parsed);
+
+        // a key repeated within a poll is still a single press
+        let mut pressed: Vec<Key> = Vec::with_capacity(keys.len());
+        for key in keys {
+            if !pressed.contains(&key) {
+                pressed.push(key);
+            }
+        }
+
+        events.extend(
+            self.pressed
+                .iter()
+                .filter(|key| !pressed.contains(key))
+                .copied()
+                .map(InputEvent::KeyReleased),
+        );
+        events.extend(
+            pressed
+                .iter()
+                .filter(|key| !self.pressed.contains(key))
+                .copied()
+                .map(InputEvent::KeyPressed),
+        );
+
+        self.pressed = pressed;
+    }
+}
+
+impl Drop for TerminalInput {
+    fn drop(&mut self) {
+        // nothing else can be done if the terminal can't be restored
+        let _ = stty(&[&self.saved_mode]);
+    }
+}
+
+/// Turns the bytes a terminal sent into the keys that were pressed
+///
+/// Returns how many bytes were parsed, any bytes left over are the start of an escape
+/// sequence the terminal hasn't finished sending
+fn parse_keys(bytes: &[u8], keys: &mut Vec<Key>) -> usize {
+    let mut index = 0;
+
+    while let Some(&byte) = bytes.get(index) {
+        index += 1;
+
+        match byte {
+            // arrow keys and other special keys are sent as escape sequences like ESC [ A,
+            // with optional parameters such as modifiers before the final byte
+            0x1b if bytes.get(index) == Some(&b'[') => {
+                let sequence = &bytes[index + 1..];
+                let Some(end) = sequence
+                    .iter()
+                    .position(|byte| !(0x20..=0x3f).contains(byte))
+                else {
+                    return index - 1;
+                };
+
+                let last = sequence[end];
+                keys.extend(match last {
+                    b'A' => Some(Key::Up),
+                    b'B' => Some(Key::Down),
+                    b'C' => Some(Key::Right),
+                    b'D' => Some(Key::Left),
+                    // other special keys are ignored
+                    _ => None,
+                });
+
+                // a byte that can't end a sequence isn't part of it and is parsed on its own
+                index += 1 + end + usize::from((0x40..=0x7e).contains(&last));
+            }
+            // terminals send escape sequences all at once, so an escape on its own is the escape key
+            0x1b => keys.push(Key::Escape),
+            0x03 => keys.extend([Key::LeftControl, Key::C]),
+            b'\r' | b'\n' => keys.push(Key::Enter),
+            b'\t' => keys.push(Key::Tab),
+            b' ' => keys.push(Key::Space),
+            0x08 | 0x7f => keys.push(Key::Backspace),
+            byte if byte.is_ascii_alphanumeric() => {
+                if byte.is_ascii_uppercase() {
+                    keys.push(Key::LeftShift);
+                }
+                // letters and digits are named after themselves
+                if let Ok(key) = (byte.to_ascii_lowercase() as char).to_string().parse() {
+                    keys.push(key);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    index
+}
+
+/// Runs stty on the terminal and returns what it printed
+pub(crate) fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::other("stty failed, stdin is not a terminal"));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(bytes: &[u8]) -> (Vec<Key>, usize) {
+        let mut keys = Vec::new();
+        let parsed = parse_keys(bytes, &mut keys);
+        (keys, parsed)
+    }
+
+    #[test]
+    fn parses_keys_from_raw_bytes() {
+        let cases: &[(&[u8], &[Key])] = &[
+            (b"a", &[Key::A]),
+            (b"Z7", &[Key::LeftShift, Key::Z, Key::Num7]),
+            (
+                b"\r\n\t \x7f\x08",
+                &[
+                    Key::Enter,
+                    Key::Enter,
+                    Key::Tab,
+                    Key::Space,
+                    Key::Backspace,
+                    Key::Backspace,
+                ],
+            ),
+            (b"\x03", &[Key::LeftControl, Key::C]),
+            (
+                b"\x1b[A\x1b[B\x1b[C\x1b[D",
+                &[Key::Up, Key::Down, Key::Right, Key::Left],
+            ),
+            (b"\x1b", &[Key::Escape]),
+            (b"\x1b\x1b[A", &[Key::Escape, Key::Up]),
+            (b"\x1bq", &[Key::Escape, Key::Q]),
+            // modifiers are ignored, as are special keys without a Key
+            (b"\x1b[1;5C", &[Key::Right]),
+            (b"\x1b[3~x", &[Key::X]),
+            (b"\x1b[2\rb", &[Key::Enter, Key::B]),
+            (b"!\x00\xff", &[]),
+        ];
+
+        for (bytes, keys) in cases {
+            assert_eq!(parse(bytes), (keys.to_vec(), bytes.len()), "{bytes:?}");
+        }
+    }
+
+    #[test]
+    fn leaves_unfinished_escape_sequences_unparsed() {
+        assert_eq!(parse(b"\x1b["), (vec![], 0));
+        assert_eq!(parse(b"w\x1b[1;5"), (vec![Key::W], 1));
+
+        // the rest of the sequence arrives with the next read
+        let mut unparsed = b"a\x1b[".to_vec();
+        let mut keys = Vec::new();
+        let parsed = parse_keys(&unparsed, &mut keys);
+        unparsed.drain(..parsed);
+        unparsed.extend(b"Ab");
+        parse_keys(&unparsed, &mut keys);
+
+        assert_eq!(keys, [Key::A, Key::Up, Key::B]);
+    }
+}
@@ -1,64 +1,361 @@
+mod config;
 mod ecs;
+mod input;
+mod log;
+mod platform;
+mod profiler;
+mod render;
 mod scene;
+mod stats;
 mod thread_pool;
 mod timer;
+mod tween;
 
 use std::{
-    sync::Arc,
+    collections::VecDeque,
+    error,
+    future::Future,
+    io,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
     time::{Duration, Instant},
 };
 
-pub use ecs::{Component, Entity, System};
-pub use scene::Scene;
+pub use config::{EngineConfig, Error as ConfigError, ErrorKind as ConfigErrorKind};
+pub use ecs::{Component, Entity, Error, ErrorAction, ErrorKind, ParallelSystem, System};
+use input::InputRecorder;
+pub use input::{
+    ActionMap, Binding, Error as InputError, ErrorKind as InputErrorKind, GamepadAxis,
+    GamepadButton, Input, InputEvent, InputRecording, InputSource, Key, MouseButton, RecordedFrame,
+    ScriptedInput, TerminalInput,
+};
+pub use log::{FileSink, Level, LogSink, Logger, Record, RingBufferSink, StderrSink};
+pub use platform::{NullPlatform, Platform, PlatformEvent};
+pub use profiler::{Profiler, TimingSummary};
+pub use render::{
+    Animation, AnimationClip, AnimationFrame, AnimationSystem, Camera, Color, Error as RenderError,
+    ErrorKind as RenderErrorKind, Glyph, Image, LoopMode, Parent, Rect, RenderLayer, Renderer,
+    Shape, SoftwareRenderer, Sprite, SpriteSheet, TerminalRenderer, Transform,
+};
 use scene::SceneManager;
-use thread_pool::ThreadPool;
-use timer::Timer;
+pub use scene::{Scene, Transition, TransitionKind};
+pub use stats::{EngineStats, SceneStats};
+pub use thread_pool::{JobError, JobGroup, JobHandle, Scope, ShutdownPolicy, Task, ThreadPool};
+use timer::IntervalTimer;
+pub use timer::{Timer, TimerHandle, TimerInterval};
+pub use tween::{Easing, Lerp, Tween, TweenSystem};
 
 use std::sync::Mutex;
 
+/// Decides what the engine does after an error
+pub type ErrorHandler = dyn Fn(&Error) -> ErrorAction + Send + Sync;
+
 pub struct Engine {
+    config: EngineConfig,
     scene_manager: SceneManager,
-    physics_timer: Mutex<Timer>,
+    physics_timer: Mutex<IntervalTimer>,
+    thread_pool: ThreadPool,
+    profiler: Profiler,
+    stats: EngineStats,
+    logger: Arc<Logger>,
+    input: Input,
+    input_source: Mutex<Option<Box<dyn InputSource>>>,
+    platform: Mutex<Box<dyn Platform>>,
+    /// The platform's window size, updated when the window is resized
+    window_size: Mutex<Option<(u32, u32)>>,
+    renderer: Mutex<Option<Box<dyn Renderer>>>,
+    recorder: Mutex<Option<InputRecorder>>,
+    /// Frames left to replay, replaces the clock, physics timer and input source
+    replay: Mutex<Option<VecDeque<RecordedFrame>>>,
+    error_handler: Box<ErrorHandler>,
+    is_running: AtomicBool,
+    /// The error that stopped the engine, returned from run()
+    abort_error: Mutex<Option<Error>>,
 }
 
 impl Engine {
     pub fn new() -> Self {
-        Self {
-            scene_manager: SceneManager::new(),
-            physics_timer: Mutex::new(Timer::new(Duration::from_secs_f64(1.0 / 60.0))),
+        Self::with_config(EngineConfig::default())
+            .expect("Failed to create engine with the default config!")
+    }
+
+    /// Creates an engine with the given settings
+    ///
+    /// # Errors
+    /// Returns an error if a setting is out of range, see EngineConfig::validate()
+    pub fn with_config(config: EngineConfig) -> Result<Self, ConfigError> {
+        config.validate()?;
+
+        let logger = Arc::new(Logger::new(config.log_level));
+        for (target, level) in config.log_levels.iter() {
+            logger.set_target_level(target, *level);
         }
+        logger.add_sink(Arc::new(StderrSink));
+
+        let thread_pool = ThreadPool::new(config.worker_count);
+        thread_pool.set_logger(Arc::clone(&logger));
+
+        let error_action = config.error_action;
+
+        let recorder =
+            config
+                .record_input
+                .as_ref()
+                .and_then(|path| match InputRecorder::create(path) {
+                    Ok(recorder) => Some(recorder),
+                    Err(err) => {
+                        logger.error(
+                            "input",
+                            format_args!("failed to create recording {}: {err}", path.display()),
+                        );
+                        None
+                    }
+                });
+
+        let replay = config
+            .replay_input
+            .as_ref()
+            .and_then(|path| match InputRecording::from_file(path) {
+                Ok(recording) => Some(recording.into_frames().into()),
+                Err(err) => {
+                    logger.error(
+                        "input",
+                        format_args!("failed to load recording {}: {err}", path.display()),
+                    );
+                    None
+                }
+            });
+
+        let input = Input::new();
+        if let Some(path) = &config.input_bindings {
+            match ActionMap::from_file(path) {
+                Ok(action_map) => input.set_action_map(action_map),
+                Err(err) => logger.error(
+                    "input",
+                    format_args!("failed to load bindings from {}: {err}", path.display()),
+                ),
+            }
+        }
+
+        Ok(Self {
+            scene_manager: SceneManager::new(Arc::clone(&logger)),
+            physics_timer: Mutex::new(IntervalTimer::new(config.physics_interval())),
+            thread_pool,
+            logger,
+            profiler: Profiler::new(config.profiler_window),
+            stats: EngineStats::new(config.stats_window, config.worker_count),
+            input,
+            input_source: Mutex::new(None),
+            platform: Mutex::new(Box::new(NullPlatform)),
+            window_size: Mutex::new(None),
+            renderer: Mutex::new(None),
+            recorder: Mutex::new(recorder),
+            replay: Mutex::new(replay),
+            error_handler: Box::new(move |_| error_action),
+            is_running: AtomicBool::new(false),
+            abort_error: Mutex::new(None),
+            config,
+        })
+    }
+
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
     }
 
     pub fn scenes(&self) -> &SceneManager {
         &self.scene_manager
     }
 
+    /// Runs an async task on the engine's thread pool
+    ///
+    /// Systems can keep the returned task around and check it on later frames with Task::try_join()
+    pub fn spawn_task<F>(&self, future: F) -> Task<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.thread_pool.spawn_task(future)
+    }
+
+    /// The keyboard, mouse and gamepad state for the current frame
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+
+    /// Sets where the engine gets its input from, without a source no input is ever reported
+    pub fn set_input_source<S: InputSource + 'static>(&mut self, source: S) {
+        *self.input_source.get_mut().unwrap() = Some(Box::new(source));
+    }
+
+    /// Sets the window, terminal or other host the engine runs in, headless by default
+    pub fn set_platform<P: Platform + 'static>(&mut self, platform: P) {
+        *self.platform.get_mut().unwrap() = Box::new(platform);
+    }
+
+    /// The size of the platform's window in pixels, or None if it has no window
+    pub fn window_size(&self) -> Option<(u32, u32)> {
+        *self.window_size.lock().unwrap()
+    }
+
+    /// Sets the renderer that draws the active scenes after every frame, nothing is drawn without one
+    pub fn set_renderer<R: Renderer + 'static>(&mut self, renderer: R) {
+        *self.renderer.get_mut().unwrap() = Some(Box::new(renderer));
+    }
+
+    /// Records every frame's dt, physics steps and input to a file while the engine runs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be created
+    pub fn record_input<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        *self.recorder.get_mut().unwrap() = Some(InputRecorder::create(path)?);
+        Ok(())
+    }
+
+    /// Replays a recording instead of using the clock, physics timer and input source
+    ///
+    /// Frames are replayed as fast as the frame rate allows and the engine stops once
+    /// every frame has been replayed. Replays are deterministic, see Engine::is_deterministic()
+    pub fn replay_input(&mut self, recording: InputRecording) {
+        *self.replay.get_mut().unwrap() = Some(recording.into_frames().into());
+    }
+
+    /// Checks if frames run the same way every time they are given the same input
+    ///
+    /// This is the case when EngineConfig::deterministic is set, or while recording or replaying.
+    /// Deterministic engines run the systems of a scene one at a time in the order they were
+    /// registered, and parallel systems have to give the same result in any entity order
+    pub fn is_deterministic(&self) -> bool {
+        self.config.deterministic
+            || self.recorder.lock().unwrap().is_some()
+            || self.replay.lock().unwrap().is_some()
+    }
+
+    /// Timings of every system and engine stage
+    pub fn profiler(&self) -> &Profiler {
+        &self.profiler
+    }
+
+    /// The engine's logger, logs to stderr unless its sinks are changed
+    pub fn logger(&self) -> &Logger {
+        &self.logger
+    }
+
+    /// Frame, physics, scene and thread pool statistics
+    pub fn stats(&self) -> &EngineStats {
+        &self.stats
+    }
+
+    /// The thread pool shared by every scene
+    pub fn thread_pool(&self) -> &ThreadPool {
+        &self.thread_pool
+    }
+
+    /// Replaces the error handler, which by default returns the error_action from the config
+    ///
+    /// Every error from a system or from running a frame is passed to the handler,
+    /// the engine logs the error and then does what the handler returned
+    pub fn set_error_handler<F>(&mut self, handler: F)
+    where
+        F: Fn(&Error) -> ErrorAction + Send + Sync + 'static,
+    {
+        self.error_handler = Box::new(handler);
+    }
+
+    /// Checks if the engine is running its main loop
+    pub fn is_running(&self) -> bool {
+        self.is_running.load(Ordering::Relaxed)
+    }
+
+    /// Stops the engine once the current frame has finished
+    pub fn stop(&self) {
+        self.is_running.store(false, Ordering::Relaxed);
+    }
+
     pub fn create_scene(&mut self) -> Result<Scene, ecs::Error> {
         self.scene_manager.create_scene()
     }
 
-    pub fn run(self, start_scene: &Scene) {
+    /// Destroys a scene before the engine starts running
+    ///
+    /// Use SceneManager::destroy_scene() to destroy scenes from systems
+    pub fn destroy_scene(&mut self, scene: &Scene) -> Result<(), ecs::Error> {
+        self.scene_manager.free_scene(scene)
+    }
+
+    /// Runs the engine until it is stopped
+    ///
+    /// # Errors
+    ///
+    /// Returns the error that aborted the engine, or an error if the start scene doesn't exist
+    pub fn run(self, start_scene: &Scene) -> Result<(), Error> {
         let this = Arc::new(self);
 
-        this.scene_manager.set_current_scene(start_scene).unwrap();
+        this.scene_manager.set_current_scene(start_scene)?;
+
+        {
+            let mut platform = this.platform.lock().unwrap();
+            platform.start(&this).map_err(Error::other)?;
+            *this.window_size.lock().unwrap() = platform.window_size();
+        }
+
+        this.is_running.store(true, Ordering::Relaxed);
+
+        this.logger.info(
+            "engine",
+            format_args!(
+                "starting with {} workers at {} physics frames per second",
+                this.thread_pool.thread_count(),
+                this.config.physics_rate
+            ),
+        );
 
         let mut physics_timer = this.physics_timer.lock().unwrap();
 
         let mut now = Instant::now();
 
+        let mut pool_busy = this.thread_pool.busy_time();
+
         physics_timer.reset();
-        loop {
+        while this.is_running() {
             let dt = now.elapsed();
             now = Instant::now();
-            // swap scenes
-            this.scene_manager.swap_scenes(Arc::clone(&this));
 
-            let current_scene = this.scene_manager.get_current_scene().unwrap();
+            let frame_span = this.profiler.span("frame", "engine");
+
+            let frame = {
+                let _input_span = this.profiler.span("input", "engine");
+                let platform_input = this.pump_platform_events();
+                this.next_frame(&mut physics_timer, dt, platform_input)
+            };
+
+            let Some(RecordedFrame {
+                dt,
+                physics_steps,
+                events,
+            }) = frame
+            else {
+                this.logger.info("input", format_args!("replay finished"));
+                this.stop();
+                break;
+            };
+
+            this.input.update(&events);
+
+            // swap scenes
+            {
+                let _swap_span = this.profiler.span("swap_scenes", "scene");
+                if let Err(err) = this.scene_manager.swap_scenes(Arc::clone(&this), dt) {
+                    this.handle_error(err);
+                }
+            }
 
             // TODO: add asset cache
 
-            let is_physics_tick = physics_timer.tick();
-            current_scene.on_frame(Arc::clone(&this), is_physics_tick, dt);
             // TODO: Do the same thing with components
             // NOTE: note that you cannot edit data of other scenes due to the fact that it gets recreated
             // when the scene loads and destroyed when it unloads, this means the user of the engine
@@ -67,10 +364,174 @@ impl Engine {
             // that will be run right before the on_entry function of any of the systems,
             // either that needs to be documented, or it needs to be impossible to edit a scene
             // anywhere but in systems or in that function
-            current_scene
-                .cull_entities()
-                .expect("Failed to cull destroyed entities!");
+            if let Err(err) = this
+                .scene_manager
+                .on_frame(Arc::clone(&this), physics_steps, dt)
+            {
+                this.handle_error(err);
+            }
+
+            {
+                let _render_span = this.profiler.span("render", "engine");
+                this.render();
+            }
+
+            {
+                let _present_span = this.profiler.span("present", "engine");
+                if let Err(err) = this.platform.lock().unwrap().present(&this) {
+                    this.handle_error(Error::other(err));
+                }
+            }
+
+            drop(frame_span);
+
+            if let Some(min_frame_time) = this.config.min_frame_time() {
+                thread::sleep(min_frame_time.saturating_sub(now.elapsed()));
+            }
+
+            let total_pool_busy = this.thread_pool.busy_time();
+            this.stats
+                .record_frame(now.elapsed(), physics_steps, total_pool_busy - pool_busy);
+            pool_busy = total_pool_busy;
+        }
+
+        if let Err(err) = this.platform.lock().unwrap().stop(&this) {
+            this.logger
+                .error("engine", format_args!("failed to stop the platform: {err}"));
+        }
+
+        this.logger.info("engine", format_args!("stopped"));
+
+        let abort_error = this.abort_error.lock().unwrap().take();
+        abort_error.map_or(Ok(()), Err)
+    }
+
+    /// Handles the platform's events, returning the input events among them
+    fn pump_platform_events(&self) -> Vec<InputEvent> {
+        let mut platform_events = Vec::new();
+        if let Err(err) = self
+            .platform
+            .lock()
+            .unwrap()
+            .pump_events(&mut platform_events)
+        {
+            self.handle_error(Error::other(err));
+        }
+
+        let mut input_events = Vec::new();
+
+        for event in platform_events {
+            match event {
+                PlatformEvent::Input(event) => input_events.push(event),
+                PlatformEvent::CloseRequested => {
+                    self.logger
+                        .info("engine", format_args!("platform requested to close"));
+                    self.stop();
+                }
+                PlatformEvent::Resized { width, height } => {
+                    self.logger
+                        .debug("engine", format_args!("window resized to {width}x{height}"));
+                    *self.window_size.lock().unwrap() = Some((width, height));
+                }
+                PlatformEvent::Focused(is_focused) => {
+                    self.logger
+                        .debug("engine", format_args!("window focused: {is_focused}"));
+                }
+            }
         }
+
+        input_events
+    }
+
+    /// Has the renderer draw the outgoing and current scene, if there is a renderer
+    fn render(&self) {
+        let mut renderer = self.renderer.lock().unwrap();
+        let Some(renderer) = renderer.as_mut() else {
+            return;
+        };
+
+        renderer.begin_frame(self);
+        for scene in self.scene_manager.active_scenes() {
+            if let Err(err) = renderer.render_scene(self, &scene) {
+                self.handle_error(err.with_scene(scene));
+            }
+        }
+        if let Err(err) = renderer.end_frame(self) {
+            self.handle_error(Error::other(err));
+        }
+    }
+
+    /// Gathers what the next frame depends on from outside the engine, recording it if needed
+    ///
+    /// While replaying, the frame comes from the replay and None is returned once it runs out,
+    /// input from the platform and input source is ignored
+    fn next_frame(
+        &self,
+        physics_timer: &mut IntervalTimer,
+        dt: Duration,
+        platform_input: Vec<InputEvent>,
+    ) -> Option<RecordedFrame> {
+        if let Some(replay) = self.replay.lock().unwrap().as_mut() {
+            return replay.pop_front();
+        }
+
+        let mut events = platform_input;
+        if let Some(source) = self.input_source.lock().unwrap().as_mut() {
+            source.poll(&mut events);
+        }
+
+        let frame = RecordedFrame {
+            dt,
+            physics_steps: physics_timer.ticks(self.config.max_catch_up_steps),
+            events,
+        };
+
+        let mut recorder = self.recorder.lock().unwrap();
+        if let Some(Err(err)) = recorder.as_mut().map(|r| r.write_frame(&frame)) {
+            self.logger.error(
+                "input",
+                format_args!("failed to write recording, recording stopped: {err}"),
+            );
+            *recorder = None;
+        }
+
+        Some(frame)
+    }
+
+    /// Passes an error to the error handler and logs it, stopping the engine if the handler aborts
+    pub(crate) fn handle_error(&self, err: Error) -> ErrorAction {
+        let action = (self.error_handler)(&err);
+
+        // the causes aren't part of the error's message, so they are logged after it
+        let mut message = err.to_string();
+        let mut source = error::Error::source(&err);
+        while let Some(cause) = source {
+            message.push_str(&format!(" caused by: {cause}"));
+            source = cause.source();
+        }
+
+        match action {
+            ErrorAction::Log => self.logger.warn("engine", format_args!("{message}")),
+            ErrorAction::DisableSystem => match err.system() {
+                Some(system) => self
+                    .logger
+                    .error("engine", format_args!("{message}, disabling {system}")),
+                None => self.logger.error("engine", format_args!("{message}")),
+            },
+            ErrorAction::Abort => {
+                self.logger
+                    .error("engine", format_args!("{message}, stopping the engine"));
+                self.abort_error.lock().unwrap().get_or_insert(err);
+                self.stop();
+            }
+        }
+
+        action
+    }
+
+    /// Checks if an error handler has aborted the engine
+    pub(crate) fn is_aborting(&self) -> bool {
+        self.abort_error.lock().unwrap().is_some()
     }
 }
 
@@ -81,11 +542,40 @@ impl Default for Engine {
 }
 
 pub mod prelude {
+    pub use super::Animation;
+    pub use super::AnimationSystem;
+    pub use super::Camera;
+    pub use super::Color;
     pub use super::Component;
+    pub use super::Easing;
     pub use super::Engine;
+    pub use super::EngineConfig;
+    pub use super::EngineStats;
     pub use super::Entity;
+    pub use super::Error;
+    pub use super::ErrorAction;
+    pub use super::ErrorKind;
+    pub use super::Glyph;
+    pub use super::Input;
+    pub use super::Key;
+    pub use super::Level;
+    pub use super::ParallelSystem;
+    pub use super::Parent;
+    pub use super::Platform;
+    pub use super::RenderLayer;
+    pub use super::Renderer;
     pub use super::Scene;
+    pub use super::Shape;
+    pub use super::Sprite;
+    pub use super::SpriteSheet;
     pub use super::System;
+    pub use super::Task;
+    pub use super::Timer;
+    pub use super::Transform;
+    pub use super::Transition;
+    pub use super::TransitionKind;
+    pub use super::Tween;
+    pub use super::TweenSystem;
 }
 
 // Plan
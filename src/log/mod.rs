@@ -0,0 +1,266 @@
+mod sink;
+
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+pub use sink::{FileSink, LogSink, RingBufferSink, StderrSink};
+
+/// How important a log record is, from most to least important
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for Level {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(Level::Error),
+            "warn" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A single log message
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    pub level: Level,
+    /// The subsystem the record came from, such as "scene" or "thread_pool"
+    pub target: String,
+    pub message: String,
+    /// The time since the logger was created
+    pub time: Duration,
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{:>10.3}s {:<5} {}] {}",
+            self.time.as_secs_f64(),
+            self.level,
+            self.target,
+            self.message
+        )
+    }
+}
+
+struct Filters {
+    default_level: Level,
+    /// Levels for specific targets, a target also applies to its `target::child` targets
+    target_levels: HashMap<String, Level>,
+}
+
+/// Sends log records at or above a level to every registered sink
+///
+/// The engine logs to the "engine", "scene", "ecs" and "thread_pool" targets,
+/// each of which can be given its own level
+pub struct Logger {
+    start: Instant,
+    filters: RwLock<Filters>,
+    sinks: RwLock<Vec<Arc<dyn LogSink>>>,
+}
+
+impl Logger {
+    /// Creates a logger with no sinks
+    pub fn new(default_level: Level) -> Self {
+        Self {
+            start: Instant::now(),
+            filters: RwLock::new(Filters {
+                default_level,
+                target_levels: HashMap::new(),
+            }),
+            sinks: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub fn add_sink(&self, sink: Arc<dyn LogSink>) {
+        self.sinks.write().unwrap().push(sink);
+    }
+
+    /// Removes every sink, discarding any records logged afterwards
+    pub fn clear_sinks(&self) {
+        self.sinks.write().unwrap().clear();
+    }
+
+    /// Sets the level of every target without its own level
+    pub fn set_level(&self, level: Level) {
+        self.filters.write().unwrap().default_level = level;
+    }
+
+    /// Sets the level of a target and its child targets
+    pub fn set_target_level(&self, target: &str, level: Level) {
+        self.filters
+            .write()
+            .unwrap()
+            .target_levels
+            .insert(target.to_string(), level);
+    }
+
+    /// Checks if a record would be logged
+    pub fn is_enabled(&self, level: Level, target: &str) -> bool {
+        let filters = self.filters.read().unwrap();
+
+        // the most specific matching target wins
+        let target_level = filters
+            .target_levels
+            .iter()
+            .filter(|(filter, _)| {
+                target == filter.as_str()
+                    || target
+                        .strip_prefix(filter.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(filter, _)| filter.len())
+            .map(|(_, level)| *level);
+
+        level <= target_level.unwrap_or(filters.default_level)
+    }
+
+    pub fn log(&self, level: Level, target: &str, args: fmt::Arguments) {
+        if !self.is_enabled(level, target) {
+            return;
+        }
+
+        let record = Record {
+            level,
+            target: target.to_string(),
+            message: args.to_string(),
+            time: self.start.elapsed(),
+        };
+
+        for sink in self.sinks.read().unwrap().iter() {
+            sink.write(&record);
+        }
+    }
+
+    pub fn error(&self, target: &str, args: fmt::Arguments) {
+        self.log(Level::Error, target, args);
+    }
+
+    pub fn warn(&self, target: &str, args: fmt::Arguments) {
+        self.log(Level::Warn, target, args);
+    }
+
+    pub fn info(&self, target: &str, args: fmt::Arguments) {
+        self.log(Level::Info, target, args);
+    }
+
+    pub fn debug(&self, target: &str, args: fmt::Arguments) {
+        self.log(Level::Debug, target, args);
+    }
+
+    pub fn trace(&self, target: &str, args: fmt::Arguments) {
+        self.log(Level::Trace, target, args);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Logs a record to every target at every level and returns what got through
+    fn logged(logger: &Logger, targets: &[&str]) -> Vec<String> {
+        let sink = Arc::new(RingBufferSink::new(64));
+        logger.add_sink(sink.clone());
+
+        for target in targets {
+            for level in [
+                Level::Error,
+                Level::Warn,
+                Level::Info,
+                Level::Debug,
+                Level::Trace,
+            ] {
+                logger.log(level, target, format_args!("message"));
+            }
+        }
+        logger.clear_sinks();
+
+        sink.drain()
+            .into_iter()
+            .map(|record| format!("{} {}", record.target, record.level))
+            .collect()
+    }
+
+    #[test]
+    fn filters_records_by_level() {
+        let logger = Logger::new(Level::Warn);
+        assert_eq!(
+            logged(&logger, &["engine"]),
+            ["engine ERROR", "engine WARN"]
+        );
+
+        logger.set_level(Level::Error);
+        assert_eq!(logged(&logger, &["engine"]), ["engine ERROR"]);
+        assert!(!logger.is_enabled(Level::Warn, "engine"));
+    }
+
+    #[test]
+    fn target_levels_apply_to_child_targets() {
+        let logger = Logger::new(Level::Error);
+        logger.set_target_level("engine::ecs", Level::Info);
+        logger.set_target_level("engine::ecs::system", Level::Warn);
+
+        assert_eq!(
+            logged(&logger, &["engine", "engine::ecs", "engine::ecs::entity"]),
+            [
+                "engine ERROR",
+                "engine::ecs ERROR",
+                "engine::ecs WARN",
+                "engine::ecs INFO",
+                "engine::ecs::entity ERROR",
+                "engine::ecs::entity WARN",
+                "engine::ecs::entity INFO",
+            ]
+        );
+        // the most specific target wins, even when it is stricter
+        assert!(!logger.is_enabled(Level::Info, "engine::ecs::system::physics"));
+        assert!(logger.is_enabled(Level::Warn, "engine::ecs::system::physics"));
+    }
+
+    #[test]
+    fn target_prefixes_only_match_whole_targets() {
+        let logger = Logger::new(Level::Error);
+        logger.set_target_level("engine::ecs", Level::Trace);
+
+        assert_eq!(
+            logged(&logger, &["engine::ecsfoo"]),
+            ["engine::ecsfoo ERROR"]
+        );
+        assert!(!logger.is_enabled(Level::Warn, "engine::ec"));
+        assert!(!logger.is_enabled(Level::Warn, "engine"));
+    }
+}
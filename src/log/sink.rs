@@ -0,0 +1,118 @@
+use std::{
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::{self, LineWriter, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+use super::Record;
+
+/// Somewhere log records are written to
+pub trait LogSink: Send + Sync {
+    fn write(&self, record: &Record);
+}
+
+/// Writes log records to stderr
+pub struct StderrSink;
+
+impl LogSink for StderrSink {
+    fn write(&self, record: &Record) {
+        eprintln!("{record}");
+    }
+}
+
+/// Appends log records to a file, one per line
+pub struct FileSink {
+    file: Mutex<LineWriter<File>>,
+}
+
+impl FileSink {
+    /// Opens a file for logging, creating it if it doesn't exist
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self {
+            file: Mutex::new(LineWriter::new(file)),
+        })
+    }
+}
+
+impl LogSink for FileSink {
+    fn write(&self, record: &Record) {
+        // there is nowhere left to report a failure to log
+        let _ = writeln!(self.file.lock().unwrap(), "{record}");
+    }
+}
+
+/// Keeps the most recent log records in memory, useful for tests and in-game consoles
+pub struct RingBufferSink {
+    capacity: usize,
+    records: Mutex<VecDeque<Record>>,
+}
+
+impl RingBufferSink {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0);
+        Self {
+            capacity,
+            records: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Retrieves the stored records, oldest first
+    pub fn records(&self) -> Vec<Record> {
+        self.records.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Removes and returns the stored records, oldest first
+    pub fn drain(&self) -> Vec<Record> {
+        self.records.lock().unwrap().drain(..).collect()
+    }
+}
+
+impl LogSink for RingBufferSink {
+    fn write(&self, record: &Record) {
+        let mut records = self.records.lock().unwrap();
+
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::Level;
+    use std::time::Duration;
+
+    fn record(message: &str) -> Record {
+        Record {
+            level: Level::Info,
+            target: "engine".to_string(),
+            message: message.to_string(),
+            time: Duration::ZERO,
+        }
+    }
+
+    fn messages(records: Vec<Record>) -> Vec<String> {
+        records.into_iter().map(|record| record.message).collect()
+    }
+
+    #[test]
+    fn ring_buffer_drops_its_oldest_records_when_full() {
+        let sink = RingBufferSink::new(3);
+        for message in ["a", "b", "c", "d", "e"] {
+            sink.write(&record(message));
+        }
+        assert_eq!(messages(sink.records()), ["c", "d", "e"]);
+
+        assert_eq!(messages(sink.drain()), ["c", "d", "e"]);
+        assert!(sink.records().is_empty());
+
+        sink.write(&record("f"));
+        assert_eq!(messages(sink.records()), ["f"]);
+    }
+}
@@ -1,46 +1,118 @@
 use std::{
+    io::{self, IsTerminal},
     sync::Arc,
-    time::{Duration, Instant},
+    time::Duration,
 };
 
-use engine::prelude::*;
+use engine::{prelude::*, RingBufferSink, TerminalInput, TerminalRenderer};
 
 // TODO: Wrap component access in a ref type
 
 fn main() {
     let mut engine = Engine::new();
 
+    // draw the entities in the terminal when there is one, otherwise print the stats
+    let terminal = match io::stdout().is_terminal() {
+        true => TerminalInput::new()
+            .and_then(|input| Ok((input, TerminalRenderer::fit_terminal()?)))
+            .ok(),
+        false => None,
+    };
+    let is_terminal = terminal.is_some();
+
+    // logs would be drawn over, so they are kept and printed once the engine stops
+    let log = Arc::new(RingBufferSink::new(256));
+    if let Some((input, renderer)) = terminal {
+        engine.set_input_source(input);
+        engine.set_renderer(renderer);
+        engine.logger().clear_sinks();
+        engine.logger().add_sink(log.clone());
+    }
+
     let test_scene = engine.create_scene().unwrap();
     let test_state = engine.scenes().get_scene(&test_scene).unwrap();
 
     let position = test_state.register_component::<Position>();
     let physics = test_state.register_component::<Physics>();
-    let fps_tracker = test_state.register_component::<FPSTracker>();
+    let stats_display = test_state.register_component::<StatsDisplay>();
+    let transform = test_state.register_component::<Transform>();
+    let shape = test_state.register_component::<Shape>();
+    test_state.register_component::<Glyph>();
 
     // TODO: Add an idea of mutability into the component reqs,
     // that way any systems that use the same components but only read them
     // can run at the same time
-    test_state.register_system(&[position, physics], PhysicsSystem);
+    test_state
+        .register_parallel_system(&[position, physics], PhysicsSystem)
+        .unwrap();
+
+    test_state
+        .register_parallel_system(&[position, transform], SyncTransformSystem)
+        .unwrap();
+
+    test_state
+        .register_system(&[stats_display, shape], StatsSystem::new(!is_terminal))
+        .unwrap();
 
-    test_state.register_system(&[fps_tracker], FpsSystem::new());
+    test_state
+        .register_system(&[stats_display], QuitSystem)
+        .unwrap();
 
-    let fps_tracker = test_state.create_entity().unwrap();
-    test_state.add_component(&fps_tracker, FPSTracker).unwrap();
+    let stats_display = test_state.create_entity().unwrap();
+    test_state
+        .add_component(&stats_display, StatsDisplay)
+        .unwrap();
+    test_state
+        .add_component(&stats_display, Transform::new(0.0, 0.0))
+        .unwrap();
+    test_state
+        .add_component(
+            &stats_display,
+            Shape::Text {
+                text: String::new(),
+                color: Color::WHITE,
+            },
+        )
+        .unwrap();
 
     let physics_count = 10;
+    let colors = [Color::RED, Color::GREEN, Color::YELLOW, Color::CYAN];
 
-    for _ in 0..physics_count {
+    for i in 0..physics_count {
         let entity = test_state.create_entity().unwrap();
         test_state
-            .add_component(&entity, Position { x: 0, y: 0 })
+            .add_component(&entity, Position { x: 0, y: i * 2 + 2 })
             .unwrap();
 
         test_state
-            .add_component(&entity, Physics { dx: 1, dy: 1 })
+            .add_component(
+                &entity,
+                Physics {
+                    dx: 1 + i % 3,
+                    dy: 0,
+                },
+            )
+            .unwrap();
+
+        test_state
+            .add_component(&entity, Transform::default())
+            .unwrap();
+        test_state
+            .add_component(
+                &entity,
+                Glyph::new('@').with_color(colors[i as usize % colors.len()]),
+            )
             .unwrap();
     }
 
-    engine.run(&test_scene);
+    let result = engine.run(&test_scene);
+
+    for record in log.drain() {
+        eprintln!("{record}");
+    }
+    if let Err(err) = result {
+        eprintln!("{err}");
+    }
 }
 
 struct Position {
@@ -53,63 +125,133 @@ struct Physics {
     pub dy: i32,
 }
 
-struct FPSTracker;
+struct StatsDisplay;
 
 struct PhysicsSystem;
 
-impl System for PhysicsSystem {
-    fn on_physics_frame(&mut self, engine: Arc<engine::Engine>, entity: Entity) {
-        let current_scene = engine.scenes().get_current_scene().unwrap();
+impl ParallelSystem for PhysicsSystem {
+    fn on_physics_frame(&self, engine: Arc<engine::Engine>, entity: Entity) -> Result<(), Error> {
+        let current_scene = engine.scenes().get_current_scene()?;
 
-        let mut pos = current_scene.get_component::<Position>(&entity).unwrap();
-        let phy = current_scene.get_component::<Physics>(&entity).unwrap();
+        let mut pos = current_scene.get_component::<Position>(&entity)?;
+        let phy = current_scene.get_component::<Physics>(&entity)?;
 
         pos.x += phy.dx;
         pos.y += phy.dy;
 
         if pos.x > 120 {
-            engine
-                .scenes()
-                .get_current_scene()
-                .unwrap()
-                .destroy_entity(entity)
-                .unwrap();
+            current_scene.destroy_entity(entity)?;
         }
+
+        Ok(())
     }
 }
 
-pub struct FpsSystem {
-    time_of_last: Instant,
-    time_of_last_phys: Instant,
+/// Moves the transforms that are drawn to the physics positions
+struct SyncTransformSystem;
+
+impl ParallelSystem for SyncTransformSystem {
+    fn on_frame(
+        &self,
+        engine: Arc<engine::Engine>,
+        entity: Entity,
+        _dt: Duration,
+    ) -> Result<(), Error> {
+        let current_scene = engine.scenes().get_current_scene()?;
+
+        let pos = current_scene.get_component::<Position>(&entity)?;
+        let mut transform = current_scene.get_component::<Transform>(&entity)?;
+
+        transform.x = pos.x as f32;
+        transform.y = pos.y as f32;
+
+        Ok(())
+    }
 }
 
-impl FpsSystem {
-    pub fn new() -> Self {
-        Self {
-            time_of_last: Instant::now(),
-            time_of_last_phys: Instant::now(),
+/// Stops the engine when escape, q or ctrl+c is pressed
+struct QuitSystem;
+
+impl System for QuitSystem {
+    fn on_frame(
+        &mut self,
+        engine: Arc<engine::Engine>,
+        _entity: Entity,
+        _dt: Duration,
+    ) -> Result<(), Error> {
+        let input = engine.input();
+
+        if input.is_key_just_pressed(Key::Escape)
+            || input.is_key_just_pressed(Key::Q)
+            || (input.is_key_pressed(Key::LeftControl) && input.is_key_pressed(Key::C))
+        {
+            engine.stop();
         }
+
+        Ok(())
     }
 }
 
-impl System for FpsSystem {
-    fn on_frame(&mut self, _engine: Arc<engine::Engine>, _entity: Entity, dt: Duration) {
-        let now = Instant::now();
-        let elapsed = now.duration_since(self.time_of_last);
-        println!("FPS: {}", 1.0 / elapsed.as_secs_f32());
-        self.time_of_last = now;
+/// Shows the engine stats once a second, printing them if they aren't drawn
+pub struct StatsSystem {
+    time_since_print: Duration,
+    print: bool,
+}
+
+impl StatsSystem {
+    pub fn new(print: bool) -> Self {
+        Self {
+            time_since_print: Duration::ZERO,
+            print,
+        }
     }
+}
+
+impl System for StatsSystem {
+    fn on_frame(
+        &mut self,
+        engine: Arc<engine::Engine>,
+        entity: Entity,
+        dt: Duration,
+    ) -> Result<(), Error> {
+        self.time_since_print += dt;
+
+        if self.time_since_print < Duration::from_secs(1) {
+            return Ok(());
+        }
+        self.time_since_print = Duration::ZERO;
+
+        let stats = engine.stats();
+        let entity_count = engine
+            .scenes()
+            .get_current_scene_handle()
+            .ok()
+            .and_then(|scene| stats.entity_count(&scene))
+            .unwrap_or(0);
+
+        let line = format!(
+            "FPS: {:.1}  PFPS: {:.1}  Entities: {}  Pool: {:.0}%",
+            stats.frames_per_second(),
+            stats.physics_rate(),
+            entity_count,
+            stats.thread_pool_utilization() * 100.0,
+        );
+
+        if self.print {
+            println!("{line}");
+        }
+
+        let current_scene = engine.scenes().get_current_scene()?;
+        if let Shape::Text { text, .. } = &mut *current_scene.get_component::<Shape>(&entity)? {
+            *text = line;
+        }
 
-    fn on_physics_frame(&mut self, _engine: Arc<engine::Engine>, _entity: Entity) {
-        let now = Instant::now();
-        let elapsed = now.duration_since(self.time_of_last_phys);
-        println!("    PFPS: {}", 1.0 / elapsed.as_secs_f32());
-        self.time_of_last_phys = now;
+        Ok(())
     }
 }
 
-impl Default for FpsSystem {
+impl Default for StatsSystem {
     fn default() -> Self {
-        Self::new()
+        Self::new(true)
     }
 }
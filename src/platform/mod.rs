@@ -0,0 +1,176 @@
+use std::io;
+
+use crate::{input::InputEvent, Engine};
+
+/// Something the operating system or window reported to the engine
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlatformEvent {
+    /// Input that is passed on to Engine::input()
+    Input(InputEvent),
+    /// The window was closed or the program was asked to quit, the engine stops after the frame
+    CloseRequested,
+    Resized {
+        width: u32,
+        height: u32,
+    },
+    /// The window gained or lost focus
+    Focused(bool),
+}
+
+/// The window, terminal or other host the engine runs in
+///
+/// The engine starts the platform when it starts running, pumps its events at the
+/// start of every frame, has it present once the frame has been run and stops
+/// it when the engine stops
+pub trait Platform: Send {
+    /// Runs once before the first frame, such as to open a window
+    fn start(&mut self, _engine: &Engine) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Runs once after the last frame, even if a frame failed
+    fn stop(&mut self, _engine: &Engine) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Adds every event that happened since the last call to events, in the order they happened
+    fn pump_events(&mut self, events: &mut Vec<PlatformEvent>) -> io::Result<()>;
+
+    /// Shows the frame that was just run
+    fn present(&mut self, engine: &Engine) -> io::Result<()>;
+
+    /// The size of the window in pixels, or None if the platform has no window
+    fn window_size(&self) -> Option<(u32, u32)> {
+        None
+    }
+}
+
+/// A platform with no window and no events, used for headless runs
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NullPlatform;
+
+impl Platform for NullPlatform {
+    fn pump_events(&mut self, _events: &mut Vec<PlatformEvent>) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn present(&mut self, _engine: &Engine) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{
+        ecs,
+        input::{InputEvent, Key},
+        render::Renderer,
+        Scene,
+    };
+
+    type Calls = Arc<Mutex<Vec<String>>>;
+
+    /// Records every call the engine makes, asking to close on the third frame
+    struct RecordingPlatform {
+        calls: Calls,
+        frames: u32,
+    }
+
+    impl Platform for RecordingPlatform {
+        fn start(&mut self, _engine: &Engine) -> io::Result<()> {
+            self.calls.lock().unwrap().push("start".to_owned());
+            Ok(())
+        }
+
+        fn stop(&mut self, _engine: &Engine) -> io::Result<()> {
+            self.calls.lock().unwrap().push("stop".to_owned());
+            Ok(())
+        }
+
+        fn pump_events(&mut self, events: &mut Vec<PlatformEvent>) -> io::Result<()> {
+            self.frames += 1;
+            self.calls.lock().unwrap().push("pump".to_owned());
+
+            match self.frames {
+                2 => events.extend([
+                    PlatformEvent::Input(InputEvent::KeyPressed(Key::Space)),
+                    PlatformEvent::Resized {
+                        width: 4,
+                        height: 3,
+                    },
+                ]),
+                3 => events.push(PlatformEvent::CloseRequested),
+                _ => {}
+            }
+            Ok(())
+        }
+
+        fn present(&mut self, engine: &Engine) -> io::Result<()> {
+            let space = engine.input().is_key_pressed(Key::Space);
+            let size = engine.window_size();
+            let call = format!("present space={space} size={size:?}");
+            self.calls.lock().unwrap().push(call);
+            Ok(())
+        }
+    }
+
+    struct RecordingRenderer {
+        calls: Calls,
+    }
+
+    impl Renderer for RecordingRenderer {
+        fn render_scene(&mut self, _engine: &Engine, _scene: &Scene) -> Result<(), ecs::Error> {
+            self.calls.lock().unwrap().push("render".to_owned());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn pumps_and_presents_every_frame_until_asked_to_close() {
+        let calls = Calls::default();
+        let mut engine = Engine::new();
+        engine.set_platform(RecordingPlatform {
+            calls: Arc::clone(&calls),
+            frames: 0,
+        });
+        engine.set_renderer(RecordingRenderer {
+            calls: Arc::clone(&calls),
+        });
+
+        let scene = engine.create_scene().unwrap();
+        engine.run(&scene).unwrap();
+
+        // the frame that asked to close still runs to the end
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [
+                "start",
+                "pump",
+                "render",
+                "present space=false size=None",
+                "pump",
+                "render",
+                "present space=true size=Some((4, 3))",
+                "pump",
+                "render",
+                // keys stay pressed until the platform reports their release
+                "present space=true size=Some((4, 3))",
+                "stop",
+            ]
+        );
+    }
+
+    #[test]
+    fn null_platform_has_no_events_or_window() {
+        let mut platform = NullPlatform;
+        let mut events = Vec::new();
+
+        platform.pump_events(&mut events).unwrap();
+        assert!(events.is_empty());
+        assert_eq!(platform.window_size(), None);
+        platform.present(&Engine::new()).unwrap();
+    }
+}
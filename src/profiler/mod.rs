@@ -0,0 +1,333 @@
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    collections::{HashMap, VecDeque},
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+thread_local! {
+    /// Small, stable thread ids for trace events, std's thread ids can't be turned into numbers
+    static THREAD_ID: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(0);
+
+fn current_thread_id() -> u64 {
+    THREAD_ID.get().unwrap_or_else(|| {
+        let id = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+        THREAD_ID.set(Some(id));
+        id
+    })
+}
+
+/// Min, average and max time of the most recent samples of a span
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimingSummary {
+    pub min: Duration,
+    pub avg: Duration,
+    pub max: Duration,
+    /// The number of samples the summary is made from
+    pub samples: usize,
+}
+
+struct TraceEvent {
+    name: Cow<'static, str>,
+    category: &'static str,
+    start: Duration,
+    duration: Duration,
+    thread_id: u64,
+}
+
+/// Records how long systems and engine stages take
+///
+/// Timings are always kept in a rolling window for summaries, full trace events
+/// are only kept between Profiler::start_recording() and Profiler::stop_recording()
+pub struct Profiler {
+    start: Instant,
+    window: usize,
+    is_recording: AtomicBool,
+    events: Mutex<Vec<TraceEvent>>,
+    samples: Mutex<HashMap<Cow<'static, str>, VecDeque<Duration>>>,
+}
+
+impl Profiler {
+    /// Creates a profiler that summarizes the last window samples of every span
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0);
+        Self {
+            start: Instant::now(),
+            window,
+            is_recording: AtomicBool::new(false),
+            events: Mutex::new(Vec::new()),
+            samples: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Starts a span that is recorded when the returned guard is dropped
+    pub(crate) fn span(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        category: &'static str,
+    ) -> ProfileSpan<'_> {
+        ProfileSpan {
+            profiler: self,
+            name: Some(name.into()),
+            trace_name: None,
+            category,
+            start: Instant::now(),
+        }
+    }
+
+    /// Starts a span like Profiler::span(), with a more detailed name for its trace event
+    ///
+    /// The trace name is only built while recording, so spans started every frame don't allocate
+    pub(crate) fn detailed_span(
+        &self,
+        name: &'static str,
+        category: &'static str,
+        trace_name: impl FnOnce() -> String,
+    ) -> ProfileSpan<'_> {
+        let mut span = self.span(name, category);
+        span.trace_name = self.is_recording().then(trace_name);
+        span
+    }
+
+    fn record(
+        &self,
+        name: Cow<'static, str>,
+        trace_name: Option<String>,
+        category: &'static str,
+        start: Instant,
+    ) {
+        let duration = start.elapsed();
+
+        if self.is_recording() {
+            self.events.lock().unwrap().push(TraceEvent {
+                name: trace_name.map_or_else(|| name.clone(), Cow::Owned),
+                category,
+                start: start.duration_since(self.start),
+                duration,
+                thread_id: current_thread_id(),
+            });
+        }
+
+        let mut samples = self.samples.lock().unwrap();
+        let samples = samples.entry(name).or_default();
+
+        if samples.len() == self.window {
+            samples.pop_front();
+        }
+        samples.push_back(duration);
+    }
+
+    /// Clears any recorded trace events and starts recording new ones
+    pub fn start_recording(&self) {
+        self.events.lock().unwrap().clear();
+        self.is_recording.store(true, Ordering::Release);
+    }
+
+    /// Stops recording trace events, the events recorded so far are kept
+    pub fn stop_recording(&self) {
+        self.is_recording.store(false, Ordering::Release);
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.is_recording.load(Ordering::Acquire)
+    }
+
+    /// Retrieves the timing summary of a span, such as "frame" or a system's type name
+    ///
+    /// A system's summary covers all of its methods, its trace events are
+    /// named "{type}::{method}" to tell them apart
+    pub fn summary(&self, name: &str) -> Option<TimingSummary> {
+        self.samples.lock().unwrap().get(name).and_then(summarize)
+    }
+
+    /// Retrieves the timing summaries of every span that has been recorded
+    pub fn summaries(&self) -> HashMap<String, TimingSummary> {
+        self.samples
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(name, samples)| Some((name.to_string(), summarize(samples)?)))
+            .collect()
+    }
+
+    /// Writes the recorded trace events in the Chrome Trace Event format
+    ///
+    /// The output can be opened in chrome://tracing or https://ui.perfetto.dev
+    pub fn write_chrome_trace<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let events = self.events.lock().unwrap();
+
+        write!(writer, "{{\"traceEvents\":[")?;
+
+        for (index, event) in events.iter().enumerate() {
+            if index > 0 {
+                write!(writer, ",")?;
+            }
+
+            write!(
+                writer,
+                "{{\"name\":\"{}\",\"cat\":\"{}\",\"ph\":\"X\",\"ts\":{},\"dur\":{},\"pid\":0,\"tid\":{}}}",
+                escape_json(&event.name),
+                escape_json(event.category),
+                event.start.as_micros(),
+                event.duration.as_micros(),
+                event.thread_id,
+            )?;
+        }
+
+        write!(writer, "],\"displayTimeUnit\":\"ms\"}}")?;
+        writer.flush()
+    }
+
+    /// Saves the recorded trace events to a file in the Chrome Trace Event format
+    pub fn save_chrome_trace<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_chrome_trace(BufWriter::new(File::create(path)?))
+    }
+}
+
+/// A span being timed, recorded when dropped
+pub(crate) struct ProfileSpan<'a> {
+    profiler: &'a Profiler,
+    name: Option<Cow<'static, str>>,
+    /// The name of the span's trace event if it differs from its name
+    trace_name: Option<String>,
+    category: &'static str,
+    start: Instant,
+}
+
+impl Drop for ProfileSpan<'_> {
+    fn drop(&mut self) {
+        if let Some(name) = self.name.take() {
+            self.profiler
+                .record(name, self.trace_name.take(), self.category, self.start);
+        }
+    }
+}
+
+fn summarize(samples: &VecDeque<Duration>) -> Option<TimingSummary> {
+    let min = *samples.iter().min()?;
+    let max = *samples.iter().max()?;
+    let avg = samples.iter().sum::<Duration>() / samples.len() as u32;
+
+    Some(TimingSummary {
+        min,
+        avg,
+        max,
+        samples: samples.len(),
+    })
+}
+
+fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_spans_are_recorded_inside_their_parent() {
+        let profiler = Profiler::new(8);
+        profiler.start_recording();
+        {
+            let _outer = profiler.span("outer", "test");
+            let _inner = profiler.span("inner", "test");
+        }
+        profiler.stop_recording();
+        let _ignored = profiler.span("ignored", "test");
+
+        let events = profiler.events.lock().unwrap();
+        let names = events.iter().map(|event| &*event.name).collect::<Vec<_>>();
+        assert_eq!(names, ["inner", "outer"]);
+
+        let (inner, outer) = (&events[0], &events[1]);
+        assert!(outer.start <= inner.start);
+        assert!(outer.start + outer.duration >= inner.start + inner.duration);
+        assert_eq!(inner.thread_id, outer.thread_id);
+    }
+
+    #[test]
+    fn summaries_cover_the_most_recent_samples() {
+        let ms = Duration::from_millis(1);
+        let samples = VecDeque::from([ms * 4, ms, ms * 7]);
+        assert_eq!(
+            summarize(&samples),
+            Some(TimingSummary {
+                min: ms,
+                avg: ms * 4,
+                max: ms * 7,
+                samples: 3,
+            })
+        );
+        assert_eq!(summarize(&VecDeque::new()), None);
+
+        let profiler = Profiler::new(2);
+        for _ in 0..3 {
+            drop(profiler.span("frame", "test"));
+        }
+        drop(profiler.span(String::from("render"), "test"));
+
+        assert_eq!(profiler.summary("frame").unwrap().samples, 2);
+        assert_eq!(profiler.summary("render").unwrap().samples, 1);
+        assert_eq!(profiler.summary("missing"), None);
+        assert_eq!(profiler.summaries().len(), 2);
+    }
+
+    #[test]
+    fn detailed_names_are_only_built_while_recording() {
+        let profiler = Profiler::new(4);
+        drop(profiler.detailed_span("System", "system", || unreachable!()));
+
+        profiler.start_recording();
+        drop(profiler.detailed_span("System", "system", || "System::on_frame".to_string()));
+
+        assert_eq!(profiler.summary("System").unwrap().samples, 2);
+        assert_eq!(profiler.events.lock().unwrap()[0].name, "System::on_frame");
+    }
+
+    #[test]
+    fn writes_chrome_trace_events() {
+        let profiler = Profiler::new(4);
+
+        let mut empty = Vec::new();
+        profiler.write_chrome_trace(&mut empty).unwrap();
+        assert_eq!(
+            String::from_utf8(empty).unwrap(),
+            r#"{"traceEvents":[],"displayTimeUnit":"ms"}"#
+        );
+
+        profiler.start_recording();
+        drop(profiler.span("say \"hi\"", "test"));
+        drop(profiler.span("frame", "engine"));
+
+        let mut trace = Vec::new();
+        profiler.write_chrome_trace(&mut trace).unwrap();
+        let trace = String::from_utf8(trace).unwrap();
+
+        assert!(trace
+            .starts_with(r#"{"traceEvents":[{"name":"say \"hi\"","cat":"test","ph":"X","ts":"#));
+        assert!(trace.contains(r#"},{"name":"frame","cat":"engine","ph":"X","ts":"#));
+        assert!(trace.ends_with(r#"}],"displayTimeUnit":"ms"}"#));
+        assert_eq!(trace.matches(r#""pid":0,"tid":"#).count(), 2);
+    }
+}
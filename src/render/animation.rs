@@ -0,0 +1,333 @@
+use std::{sync::Arc, time::Duration};
+
+use super::{AnimationClip, Error, ErrorKind, LoopMode, Rect, Sprite, SpriteSheet};
+use crate::{ecs, Engine, Entity, ParallelSystem};
+
+/// Plays clips of a sprite sheet, advanced by the AnimationSystem
+#[derive(Clone, Debug)]
+pub struct Animation {
+    sheet: Arc<SpriteSheet>,
+    clip: String,
+    /// the index of the current frame in the clip
+    frame: usize,
+    /// how long the current frame has been shown
+    elapsed: Duration,
+    /// true while a ping-pong clip plays backwards
+    is_reversed: bool,
+    is_paused: bool,
+    is_finished: bool,
+    /// false until the first frame's event has been fired
+    has_started: bool,
+    speed: f32,
+    events: Vec<String>,
+}
+
+impl Animation {
+    /// The fastest a clip can play, so scaling a frame's dt can't overflow
+    pub const MAX_SPEED: f32 = 1000.0;
+
+    /// Creates an animation playing a clip of the sprite sheet from its first frame
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sprite sheet has no clip with the name
+    pub fn new(sheet: Arc<SpriteSheet>, clip: &str) -> Result<Self, Error> {
+        if sheet.clip(clip).is_none() {
+            return Err(ErrorKind::UnknownClip(clip.to_owned()).into());
+        }
+
+        Ok(Self {
+            sheet,
+            clip: clip.to_owned(),
+            frame: 0,
+            elapsed: Duration::ZERO,
+            is_reversed: false,
+            is_paused: false,
+            is_finished: false,
+            has_started: false,
+            speed: 1.0,
+            events: Vec::new(),
+        })
+    }
+
+    /// Switches to another clip of the sprite sheet, playing it from its first frame
+    ///
+    /// Playing the clip that is already playing does nothing, use restart() to play it again
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sprite sheet has no clip with the name
+    pub fn play(&mut self, clip: &str) -> Result<(), Error> {
+        if self.clip == clip {
+            return Ok(());
+        }
+        if self.sheet.clip(clip).is_none() {
+            return Err(ErrorKind::UnknownClip(clip.to_owned()).into());
+        }
+
+        self.clip = clip.to_owned();
+        self.restart();
+        Ok(())
+    }
+
+    /// Plays the current clip again from its first frame
+    pub fn restart(&mut self) {
+        self.frame = 0;
+        self.elapsed = Duration::ZERO;
+        self.is_reversed = false;
+        self.is_finished = false;
+        self.has_started = false;
+    }
+
+    pub fn pause(&mut self) {
+        self.is_paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.is_paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    /// Checks if a clip that plays once has reached its last frame
+    pub fn is_finished(&self) -> bool {
+        self.is_finished
+    }
+
+    /// Sets how fast the clip plays, 1 is its normal speed
+    ///
+    /// The speed is clamped between 0 and MAX_SPEED, NaN stops the clip
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = match speed.is_nan() {
+            true => 0.0,
+            false => speed.clamp(0.0, Self::MAX_SPEED),
+        };
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    pub fn sheet(&self) -> &Arc<SpriteSheet> {
+        &self.sheet
+    }
+
+    /// The name of the clip that is playing
+    pub fn clip_name(&self) -> &str {
+        &self.clip
+    }
+
+    /// The index of the current frame in the clip
+    pub fn clip_frame(&self) -> usize {
+        self.frame
+    }
+
+    /// The index of the current frame in the sprite sheet
+    pub fn sheet_frame(&self) -> Option<usize> {
+        let clip = self.sheet.clip(&self.clip)?;
+        clip.frames.get(self.frame).map(|frame| frame.index)
+    }
+
+    /// The part of the sprite sheet's image the current frame covers
+    pub fn frame_rect(&self) -> Option<Rect> {
+        self.sheet.frame(self.sheet_frame()?)
+    }
+
+    /// The events of the frames reached by the last advance, in the order they were reached
+    pub fn events(&self) -> &[String] {
+        &self.events
+    }
+
+    /// Checks if the last advance reached a frame with the event
+    pub fn has_event(&self, event: &str) -> bool {
+        self.events.iter().any(|fired| fired == event)
+    }
+
+    /// Moves the animation forward by dt scaled by its speed, firing the events of every
+    /// frame it reaches along the way
+    pub fn advance(&mut self, dt: Duration) {
+        self.events.clear();
+
+        let sheet = Arc::clone(&self.sheet);
+        let Some(clip) = sheet.clip(&self.clip) else {
+            return;
+        };
+        if clip.frames.is_empty() {
+            return;
+        }
+
+        if !self.has_started {
+            self.has_started = true;
+            self.fire_event(clip);
+        }
+
+        // a clip without a duration would never reach the end of its current frame
+        if self.is_paused || self.is_finished || clip.duration().is_zero() {
+            return;
+        }
+
+        self.elapsed += dt.mul_f32(self.speed);
+
+        while self.elapsed >= clip.frames[self.frame].duration {
+            self.elapsed -= clip.frames[self.frame].duration;
+
+            if !self.step(clip) {
+                self.elapsed = Duration::ZERO;
+                self.is_finished = true;
+                break;
+            }
+            self.fire_event(clip);
+        }
+    }
+
+    /// Moves to the next frame of the clip, returning false if a clip that plays once has ended
+    fn step(&mut self, clip: &AnimationClip) -> bool {
+        let last = clip.frames.len() - 1;
+
+        match clip.mode {
+            LoopMode::Once if self.frame == last => return false,
+            LoopMode::Once => self.frame += 1,
+            LoopMode::Loop => {
+                self.frame = if self.frame == last {
+                    0
+                } else {
+                    self.frame + 1
+                }
+            }
+            LoopMode::PingPong if last == 0 => {}
+            LoopMode::PingPong => {
+                if self.is_reversed && self.frame == 0 || !self.is_reversed && self.frame == last {
+                    self.is_reversed = !self.is_reversed;
+                }
+                match self.is_reversed {
+                    true => self.frame -= 1,
+                    false => self.frame += 1,
+                }
+            }
+        }
+
+        true
+    }
+
+    fn fire_event(&mut self, clip: &AnimationClip) {
+        if let Some(event) = &clip.frames[self.frame].event {
+            self.events.push(event.clone());
+        }
+    }
+}
+
+/// Advances every Animation by the frame's dt and shows its current frame in the entity's Sprite
+///
+/// Register it with the Animation and Sprite components as its signature. Systems that
+/// react to animation events should have Animation in their signature and be registered
+/// after it, so they run after it every frame
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AnimationSystem;
+
+impl ParallelSystem for AnimationSystem {
+    fn on_frame(
+        &self,
+        engine: Arc<Engine>,
+        entity: Entity,
+        dt: Duration,
+    ) -> Result<(), ecs::Error> {
+        let scene = engine.scenes().get_current_scene()?;
+
+        let mut animation = scene.get_component::<Animation>(&entity)?;
+        let mut sprite = scene.get_component::<Sprite>(&entity)?;
+
+        animation.advance(dt);
+
+        if !Arc::ptr_eq(&sprite.image, animation.sheet.image()) {
+            sprite.image = Arc::clone(animation.sheet.image());
+        }
+        sprite.source = animation.frame_rect();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Image;
+
+    const FRAME: Duration = Duration::from_millis(100);
+
+    fn animation(mode: LoopMode) -> Animation {
+        let mut sheet = SpriteSheet::from_grid(Arc::new(Image::new(48, 16)), 16, 16);
+        let clip = AnimationClip::new(mode)
+            .with_frame(0, FRAME)
+            .with_frame(1, FRAME)
+            .with_frame(2, FRAME)
+            .with_event(2, "last");
+        sheet.add_clip("clip", clip).unwrap();
+
+        Animation::new(Arc::new(sheet), "clip").unwrap()
+    }
+
+    /// The clip frame after each of a number of advances by a frame's duration
+    fn frames(animation: &mut Animation, count: usize) -> Vec<usize> {
+        (0..count)
+            .map(|_| {
+                animation.advance(FRAME);
+                animation.clip_frame()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn steps_through_loop_modes() {
+        let mut once = animation(LoopMode::Once);
+        assert_eq!(frames(&mut once, 4), [1, 2, 2, 2]);
+        assert!(once.is_finished());
+
+        let mut looping = animation(LoopMode::Loop);
+        assert_eq!(frames(&mut looping, 5), [1, 2, 0, 1, 2]);
+        assert!(!looping.is_finished());
+
+        let mut ping_pong = animation(LoopMode::PingPong);
+        assert_eq!(frames(&mut ping_pong, 6), [1, 2, 1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn fires_the_events_of_skipped_frames() {
+        let mut animation = animation(LoopMode::Loop);
+
+        animation.advance(FRAME * 5);
+        assert_eq!(animation.clip_frame(), 2);
+        assert_eq!(animation.events(), ["last", "last"]);
+
+        animation.advance(FRAME / 2);
+        assert!(!animation.has_event("last"));
+    }
+
+    #[test]
+    fn scales_time_by_speed() {
+        let mut animation = animation(LoopMode::Loop);
+
+        animation.set_speed(2.0);
+        animation.advance(FRAME);
+        assert_eq!(animation.clip_frame(), 2);
+
+        animation.set_speed(0.0);
+        animation.advance(FRAME);
+        assert_eq!(animation.clip_frame(), 2);
+    }
+
+    #[test]
+    fn clamps_speed_to_a_finite_value() {
+        let mut animation = animation(LoopMode::Loop);
+
+        animation.set_speed(f32::INFINITY);
+        assert_eq!(animation.speed(), Animation::MAX_SPEED);
+        animation.advance(Duration::from_secs(1));
+
+        animation.set_speed(f32::NAN);
+        assert_eq!(animation.speed(), 0.0);
+        animation.set_speed(-1.0);
+        assert_eq!(animation.speed(), 0.0);
+    }
+}
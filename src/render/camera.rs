@@ -0,0 +1,220 @@
+use super::{Rect, Transform};
+
+/// Shows part of the world in a viewport of the screen
+///
+/// A scene with cameras is drawn once for every camera, in order of their entity ids, so
+/// cameras with separate viewports can be used for split-screen. Scenes without a camera
+/// are drawn with world coordinates used as screen coordinates. A camera is positioned by its
+/// own x and y, any Transform or Parent on its entity is ignored
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    /// The world position shown at the center of the viewport
+    pub x: f32,
+    pub y: f32,
+    /// Screen pixels per world unit
+    pub zoom: f32,
+    /// The part of the screen the camera draws to
+    pub viewport: Rect,
+}
+
+impl Camera {
+    /// The smallest zoom, zooming out further would fit the whole world in a pixel
+    pub const MIN_ZOOM: f32 = 0.001;
+    /// The largest zoom, zooming in further would fill the screen with a fraction of a pixel
+    pub const MAX_ZOOM: f32 = 1000.0;
+
+    /// Creates a camera that shows the world the same as if there was no camera
+    pub fn new(viewport: Rect) -> Self {
+        Self {
+            x: viewport.x as f32 + viewport.width as f32 / 2.0,
+            y: viewport.y as f32 + viewport.height as f32 / 2.0,
+            zoom: 1.0,
+            viewport,
+        }
+    }
+
+    pub fn with_position(mut self, x: f32, y: f32) -> Self {
+        self.x = x;
+        self.y = y;
+        self
+    }
+
+    /// Sets how many screen pixels a world unit covers
+    ///
+    /// The zoom is clamped between MIN_ZOOM and MAX_ZOOM, NaN keeps the camera unzoomed
+    pub fn with_zoom(mut self, zoom: f32) -> Self {
+        self.zoom = match zoom.is_nan() {
+            true => 1.0,
+            false => zoom.clamp(Self::MIN_ZOOM, Self::MAX_ZOOM),
+        };
+        self
+    }
+
+    /// The center of the viewport on screen
+    fn viewport_center(&self) -> (f32, f32) {
+        (
+            self.viewport.x as f32 + self.viewport.width as f32 / 2.0,
+            self.viewport.y as f32 + self.viewport.height as f32 / 2.0,
+        )
+    }
+
+    /// Converts a world position into a screen position
+    pub fn world_to_screen(&self, x: f32, y: f32) -> (f32, f32) {
+        let (center_x, center_y) = self.viewport_center();
+
+        (
+            (x - self.x) * self.zoom + center_x,
+            (y - self.y) * self.zoom + center_y,
+        )
+    }
+
+    /// Converts a screen position, such as the mouse position, into a world position
+    pub fn screen_to_world(&self, x: f32, y: f32) -> (f32, f32) {
+        let (center_x, center_y) = self.viewport_center();
+
+        (
+            (x - center_x) / self.zoom + self.x,
+            (y - center_y) / self.zoom + self.y,
+        )
+    }
+
+    /// Checks if a screen position lies inside the viewport
+    pub fn contains_screen_point(&self, x: f32, y: f32) -> bool {
+        self.viewport.contains(x, y)
+    }
+
+    /// Moves a transform in the world to where it is drawn on screen
+    pub fn world_to_screen_transform(&self, transform: &Transform) -> Transform {
+        let (x, y) = self.world_to_screen(transform.x, transform.y);
+
+        Transform {
+            x,
+            y,
+            rotation: transform.rotation,
+            scale_x: transform.scale_x * self.zoom,
+            scale_y: transform.scale_y * self.zoom,
+        }
+    }
+
+    /// The part of the world the camera shows, anything outside of it is culled
+    pub(super) fn visible_bounds(&self) -> Bounds {
+        let (min_x, min_y) = self.screen_to_world(self.viewport.x as f32, self.viewport.y as f32);
+        let (max_x, max_y) = self.screen_to_world(
+            self.viewport.x as f32 + self.viewport.width as f32,
+            self.viewport.y as f32 + self.viewport.height as f32,
+        );
+
+        Bounds {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        }
+    }
+}
+
+/// An axis aligned box in the world
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct Bounds {
+    pub min_x: f32,
+    pub min_y: f32,
+    pub max_x: f32,
+    pub max_y: f32,
+}
+
+impl Bounds {
+    /// The box around a rectangle in an entity's local space once it is transformed into the world
+    pub fn of_local_rect(
+        transform: &Transform,
+        min_x: f32,
+        min_y: f32,
+        max_x: f32,
+        max_y: f32,
+    ) -> Self {
+        let corners = [
+            (min_x, min_y),
+            (max_x, min_y),
+            (min_x, max_y),
+            (max_x, max_y),
+        ]
+        .map(|(x, y)| transform.transform_point(x, y));
+
+        corners.iter().fold(
+            Bounds {
+                min_x: f32::INFINITY,
+                min_y: f32::INFINITY,
+                max_x: f32::NEG_INFINITY,
+                max_y: f32::NEG_INFINITY,
+            },
+            |bounds, &(x, y)| Bounds {
+                min_x: bounds.min_x.min(x),
+                min_y: bounds.min_y.min(y),
+                max_x: bounds.max_x.max(x),
+                max_y: bounds.max_y.max(y),
+            },
+        )
+    }
+
+    pub fn intersects(&self, other: &Bounds) -> bool {
+        self.min_x <= other.max_x
+            && other.min_x <= self.max_x
+            && self.min_y <= other.max_y
+            && other.min_y <= self.max_y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn world_and_screen_positions_round_trip() {
+        let viewport = Rect::new(10, 20, 64, 48);
+
+        for zoom in [Camera::MIN_ZOOM, 0.25, 1.0, 3.0, Camera::MAX_ZOOM] {
+            let camera = Camera::new(viewport)
+                .with_position(-5.0, 7.5)
+                .with_zoom(zoom);
+
+            // the camera's position is shown at the center of its viewport
+            assert_eq!(camera.world_to_screen(-5.0, 7.5), (42.0, 44.0));
+            assert_eq!(camera.screen_to_world(42.0, 44.0), (-5.0, 7.5));
+
+            for (x, y) in [(0.0, 0.0), (-12.5, 3.0), (100.0, -40.0)] {
+                let (screen_x, screen_y) = camera.world_to_screen(x, y);
+                let (world_x, world_y) = camera.screen_to_world(screen_x, screen_y);
+                let tolerance = 1e-3 / zoom;
+                assert!((world_x - x).abs() < tolerance, "{zoom}: {world_x} != {x}");
+                assert!((world_y - y).abs() < tolerance, "{zoom}: {world_y} != {y}");
+            }
+        }
+
+        let camera = Camera::new(viewport).with_position(0.0, 0.0).with_zoom(2.0);
+        assert_eq!(camera.world_to_screen(1.0, -1.0), (44.0, 42.0));
+    }
+
+    #[test]
+    fn zoom_is_kept_positive_and_finite() {
+        let camera = Camera::new(Rect::new(0, 0, 8, 8));
+
+        assert_eq!(camera.with_zoom(0.0).zoom, Camera::MIN_ZOOM);
+        assert_eq!(camera.with_zoom(-2.0).zoom, Camera::MIN_ZOOM);
+        assert_eq!(camera.with_zoom(f32::INFINITY).zoom, Camera::MAX_ZOOM);
+        assert_eq!(camera.with_zoom(f32::NAN).zoom, 1.0);
+
+        let bounds = camera.with_zoom(-2.0).visible_bounds();
+        assert!(bounds.min_x < bounds.max_x && bounds.min_y < bounds.max_y);
+    }
+
+    #[test]
+    fn visible_bounds_of_viewports_reaching_past_u32_max() {
+        let camera = Camera::new(Rect::new(u32::MAX, 0, u32::MAX, 2)).with_position(0.0, 0.0);
+        let bounds = camera.visible_bounds();
+
+        assert_eq!(
+            (bounds.min_x, bounds.max_x),
+            (-(u32::MAX as f32) / 2.0, u32::MAX as f32 / 2.0)
+        );
+        assert_eq!((bounds.min_y, bounds.max_y), (-1.0, 1.0));
+    }
+}
@@ -0,0 +1,71 @@
+/// An 8 bit per channel RGBA color, not premultiplied
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const RED: Color = Color::rgb(255, 0, 0);
+    pub const GREEN: Color = Color::rgb(0, 255, 0);
+    pub const BLUE: Color = Color::rgb(0, 0, 255);
+    pub const YELLOW: Color = Color::rgb(255, 255, 0);
+    pub const CYAN: Color = Color::rgb(0, 255, 255);
+    pub const MAGENTA: Color = Color::rgb(255, 0, 255);
+
+    /// An opaque color
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// The same color with a different alpha
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+
+    /// Multiplies every channel with the tint's, white leaves the color unchanged
+    pub fn tinted(self, tint: Color) -> Self {
+        Self {
+            r: mul(self.r, tint.r),
+            g: mul(self.g, tint.g),
+            b: mul(self.b, tint.b),
+            a: mul(self.a, tint.a),
+        }
+    }
+
+    /// Draws this color over another with source-over alpha blending
+    pub fn blend_over(self, dst: Color) -> Self {
+        match self.a {
+            255 => return self,
+            0 => return dst,
+            _ => {}
+        }
+
+        let src_a = self.a as u32;
+        let dst_a = dst.a as u32 * (255 - src_a) / 255;
+        let out_a = src_a + dst_a;
+
+        let channel = |src: u8, dst: u8| ((src as u32 * src_a + dst as u32 * dst_a) / out_a) as u8;
+
+        Self {
+            r: channel(self.r, dst.r),
+            g: channel(self.g, dst.g),
+            b: channel(self.b, dst.b),
+            a: out_a as u8,
+        }
+    }
+}
+
+// multiplies two channels as if they were 0 to 1
+fn mul(a: u8, b: u8) -> u8 {
+    ((a as u32 * b as u32 + 127) / 255) as u8
+}
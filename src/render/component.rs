@@ -0,0 +1,229 @@
+use std::sync::Arc;
+
+use super::{Color, Image};
+use crate::Entity;
+
+/// Where an entity is drawn, in pixels with y pointing down
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub x: f32,
+    pub y: f32,
+    /// Clockwise rotation in radians
+    pub rotation: f32,
+    pub scale_x: f32,
+    pub scale_y: f32,
+}
+
+impl Transform {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self {
+            x,
+            y,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale_x: f32, scale_y: f32) -> Self {
+        self.scale_x = scale_x;
+        self.scale_y = scale_y;
+        self
+    }
+
+    /// Scales, rotates and then moves a point from the entity's local space
+    pub fn transform_point(&self, x: f32, y: f32) -> (f32, f32) {
+        let (sin, cos) = self.rotation.sin_cos();
+        let (x, y) = (x * self.scale_x, y * self.scale_y);
+
+        (x * cos - y * sin + self.x, x * sin + y * cos + self.y)
+    }
+
+    /// Combines a transform relative to a parent with the parent's transform in the world
+    ///
+    /// Scales are applied along the entity's own axes, so a rotated entity with a parent
+    /// that has a non-uniform scale isn't skewed
+    pub fn combined_with(&self, parent: &Transform) -> Transform {
+        let (x, y) = parent.transform_point(self.x, self.y);
+
+        Transform {
+            x,
+            y,
+            rotation: parent.rotation + self.rotation,
+            scale_x: parent.scale_x * self.scale_x,
+            scale_y: parent.scale_y * self.scale_y,
+        }
+    }
+
+    /// Moves a point into the entity's local space, or None if the transform has a scale of 0
+    pub fn inverse_transform_point(&self, x: f32, y: f32) -> Option<(f32, f32)> {
+        if self.scale_x == 0.0 || self.scale_y == 0.0 {
+            return None;
+        }
+
+        let (sin, cos) = self.rotation.sin_cos();
+        let (x, y) = (x - self.x, y - self.y);
+
+        Some((
+            (x * cos + y * sin) / self.scale_x,
+            (y * cos - x * sin) / self.scale_y,
+        ))
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            rotation: 0.0,
+            scale_x: 1.0,
+            scale_y: 1.0,
+        }
+    }
+}
+
+/// Makes an entity's Transform relative to the Transform of another entity in its scene
+///
+/// Parents can have parents of their own, an entity is drawn where the Transforms of all
+/// its parents put it
+#[derive(Debug)]
+pub struct Parent(pub Entity);
+
+/// Orders what is drawn, lower layers are drawn first and the z-index orders draws within a layer
+///
+/// Drawables without a RenderLayer are on layer 0 with a z-index of 0, draws that share a
+/// layer and z-index are drawn in order of their entity ids
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RenderLayer {
+    pub layer: i32,
+    pub z_index: i32,
+}
+
+impl RenderLayer {
+    pub fn new(layer: i32) -> Self {
+        Self { layer, z_index: 0 }
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+}
+
+/// A rectangle in pixels
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Checks if a point lies inside the rectangle
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x as f32
+            && y >= self.y as f32
+            && x < self.x as f32 + self.width as f32
+            && y < self.y as f32 + self.height as f32
+    }
+}
+
+/// An image drawn centered on an entity's Transform
+#[derive(Clone, Debug)]
+pub struct Sprite {
+    pub image: Arc<Image>,
+    /// The part of the image that is drawn, the whole image if None
+    pub source: Option<Rect>,
+    /// Multiplied with every pixel of the image
+    pub tint: Color,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl Sprite {
+    pub fn new(image: Arc<Image>) -> Self {
+        Self {
+            image,
+            source: None,
+            tint: Color::WHITE,
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+
+    pub fn with_source(mut self, source: Rect) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn with_tint(mut self, tint: Color) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// The part of the image that is drawn
+    pub fn source_rect(&self) -> Rect {
+        self.source
+            .unwrap_or_else(|| Rect::new(0, 0, self.image.width(), self.image.height()))
+    }
+}
+
+/// A primitive drawn at an entity's Transform
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    /// A filled rectangle centered on the transform
+    Rect {
+        width: f32,
+        height: f32,
+        color: Color,
+    },
+    /// A line from the transform to a point in the entity's local space
+    Line { dx: f32, dy: f32, color: Color },
+    /// Text in the built-in font with its top left corner at the transform
+    ///
+    /// Text is scaled by the transform's x scale rounded to a whole number and is never rotated
+    Text { text: String, color: Color },
+}
+
+/// A character drawn in the terminal cell under an entity's Transform by the TerminalRenderer
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Glyph {
+    pub ch: char,
+    pub color: Color,
+    /// The cell's background, the terminal's own background if None
+    pub background: Option<Color>,
+}
+
+impl Glyph {
+    pub fn new(ch: char) -> Self {
+        Self {
+            ch,
+            color: Color::WHITE,
+            background: None,
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_background(mut self, background: Color) -> Self {
+        self.background = Some(background);
+        self
+    }
+}
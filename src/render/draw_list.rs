@@ -0,0 +1,126 @@
+use super::{Image, RenderLayer, Shape, Sprite, Transform};
+
+/// A sprite or shape that has been moved to where it is drawn on screen
+pub(super) enum Draw {
+    Sprite { sprite: Sprite, screen: Transform },
+    Shape { shape: Shape, screen: Transform },
+}
+
+impl Draw {
+    fn draw(&self, framebuffer: &mut Image) {
+        match self {
+            Draw::Sprite { sprite, screen } => framebuffer.draw_image(
+                &sprite.image,
+                sprite.source_rect(),
+                screen,
+                sprite.tint,
+                (sprite.flip_x, sprite.flip_y),
+            ),
+            Draw::Shape { shape, screen } => match shape {
+                Shape::Rect {
+                    width,
+                    height,
+                    color,
+                } => framebuffer.fill_transformed_rect(screen, *width, *height, *color),
+                Shape::Line { dx, dy, color } => {
+                    let (x1, y1) = screen.transform_point(*dx, *dy);
+                    framebuffer.draw_line(
+                        screen.x.round() as i32,
+                        screen.y.round() as i32,
+                        x1.round() as i32,
+                        y1.round() as i32,
+                        *color,
+                    );
+                }
+                Shape::Text { text, color } => framebuffer.draw_text(
+                    screen.x.round() as i32,
+                    screen.y.round() as i32,
+                    text,
+                    screen.scale_x.round().max(1.0) as u32,
+                    *color,
+                ),
+            },
+        }
+    }
+}
+
+/// The draws of a view, sorted into the order they are drawn in
+///
+/// Draws are sorted by their render layer, draws that share a layer and z-index keep the
+/// order they were pushed in. They aren't grouped by image, drawing into the framebuffer
+/// has no per-image setup to share
+#[derive(Default)]
+pub(super) struct DrawList {
+    draws: Vec<(RenderLayer, Draw)>,
+}
+
+impl DrawList {
+    pub fn push(&mut self, layer: RenderLayer, draw: Draw) {
+        self.draws.push((layer, draw));
+    }
+
+    pub fn len(&self) -> usize {
+        self.draws.len()
+    }
+
+    /// Sorts the draws and draws them one by one
+    pub fn draw(mut self, framebuffer: &mut Image) {
+        // the sort is stable, so draws within a layer stay in the order they were pushed
+        self.draws.sort_by_key(|(layer, _)| *layer);
+
+        for (_, draw) in self.draws.iter() {
+            draw.draw(framebuffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+    use std::sync::Arc;
+
+    fn sprite(image: &Arc<Image>) -> Draw {
+        Draw::Sprite {
+            sprite: Sprite::new(Arc::clone(image)),
+            screen: Transform::new(1.0, 1.0),
+        }
+    }
+
+    /// Draws the list into a 2x2 framebuffer and returns the color of its top left pixel
+    fn top_left(draws: DrawList) -> Option<Color> {
+        let mut framebuffer = Image::new(2, 2);
+        draws.draw(&mut framebuffer);
+        framebuffer.get_pixel(0, 0)
+    }
+
+    #[test]
+    fn draws_within_a_layer_keep_their_order() {
+        let red = Arc::new(Image::filled(2, 2, Color::RED));
+        let blue = Arc::new(Image::filled(2, 2, Color::BLUE));
+
+        let mut draws = DrawList::default();
+        draws.push(RenderLayer::default(), sprite(&red));
+        draws.push(RenderLayer::default(), sprite(&blue));
+        draws.push(RenderLayer::default(), sprite(&red));
+        assert_eq!(top_left(draws), Some(Color::RED));
+
+        let mut draws = DrawList::default();
+        draws.push(RenderLayer::default(), sprite(&red));
+        draws.push(RenderLayer::default(), sprite(&blue));
+        assert_eq!(top_left(draws), Some(Color::BLUE));
+    }
+
+    #[test]
+    fn sorts_draws_by_layer() {
+        let red = Arc::new(Image::filled(2, 2, Color::RED));
+        let blue = Arc::new(Image::filled(2, 2, Color::BLUE));
+
+        let mut draws = DrawList::default();
+        draws.push(RenderLayer::new(1), sprite(&red));
+        draws.push(RenderLayer::default(), sprite(&blue));
+        draws.push(RenderLayer::default(), sprite(&blue));
+        assert_eq!(draws.len(), 3);
+        assert_eq!(top_left(draws), Some(Color::RED));
+    }
+}
@@ -0,0 +1,260 @@
+//! PPM and PNG encoding without any dependencies
+//!
+//! PNGs are written with uncompressed (stored) deflate blocks, so they are larger than
+//! they need to be but can be read by anything that reads PNGs
+
+use std::io::{self, Write};
+
+use super::{Color, Image};
+
+pub fn encode_ppm<W: Write>(image: &Image, writer: &mut W) -> io::Result<()> {
+    write!(writer, "P6\n{} {}\n255\n", image.width(), image.height())?;
+
+    let data = image
+        .pixels()
+        .iter()
+        .flat_map(|pixel| [pixel.r, pixel.g, pixel.b])
+        .collect::<Vec<_>>();
+
+    writer.write_all(&data)
+}
+
+pub fn decode_ppm(data: &[u8]) -> io::Result<Image> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_owned());
+
+    // the header is 4 whitespace separated fields, comments run to the end of the line
+    let mut fields = Vec::with_capacity(4);
+    let mut position = 0;
+
+    while fields.len() < 4 {
+        match data.get(position) {
+            None => return Err(invalid("PPM header ended early")),
+            Some(b'#') => {
+                while data.get(position).is_some_and(|&byte| byte != b'\n') {
+                    position += 1;
+                }
+            }
+            Some(byte) if byte.is_ascii_whitespace() => position += 1,
+            Some(_) => {
+                let start = position;
+                while data
+                    .get(position)
+                    .is_some_and(|byte| !byte.is_ascii_whitespace())
+                {
+                    position += 1;
+                }
+                fields.push(String::from_utf8_lossy(&data[start..position]).into_owned());
+            }
+        }
+    }
+    // a single whitespace character separates the header from the pixels
+    position += 1;
+
+    if fields[0] != "P6" {
+        return Err(invalid("only binary (P6) PPM images are supported"));
+    }
+
+    let parse = |field: &str| {
+        field
+            .parse::<u32>()
+            .map_err(|_| invalid("PPM header field is not a number"))
+    };
+    let (width, height, max_value) = (parse(&fields[1])?, parse(&fields[2])?, parse(&fields[3])?);
+
+    if max_value != 255 {
+        return Err(invalid("only PPM images with 8 bit channels are supported"));
+    }
+
+    let (pixel_count, byte_count) = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|pixel_count| Some((pixel_count, pixel_count.checked_mul(3)?)))
+        .ok_or_else(|| invalid("PPM image is too large"))?;
+    let pixels = data
+        .get(position..)
+        .filter(|pixels| pixels.len() >= byte_count)
+        .ok_or_else(|| invalid("PPM image has fewer pixels than its size"))?;
+
+    Ok(Image::from_pixels(
+        width,
+        height,
+        pixels
+            .chunks_exact(3)
+            .take(pixel_count)
+            .map(|rgb| Color::rgb(rgb[0], rgb[1], rgb[2]))
+            .collect(),
+    ))
+}
+
+pub fn encode_png<W: Write>(image: &Image, writer: &mut W) -> io::Result<()> {
+    writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend(image.width().to_be_bytes());
+    header.extend(image.height().to_be_bytes());
+    // 8 bit RGBA, default compression and filtering, not interlaced
+    header.extend([8, 6, 0, 0, 0]);
+    write_chunk(writer, b"IHDR", &header)?;
+
+    // every row starts with its filter type, 0 for none
+    let mut scanlines =
+        Vec::with_capacity((image.width() as usize * 4 + 1) * image.height() as usize);
+    for row in image.pixels().chunks(image.width().max(1) as usize) {
+        scanlines.push(0);
+        scanlines.extend(
+            row.iter()
+                .flat_map(|pixel| [pixel.r, pixel.g, pixel.b, pixel.a]),
+        );
+    }
+    write_chunk(writer, b"IDAT", &zlib_stored(&scanlines))?;
+
+    write_chunk(writer, b"IEND", &[])
+}
+
+fn write_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+
+    let crc = crc32(kind.iter().chain(data));
+    writer.write_all(&crc.to_be_bytes())
+}
+
+/// Wraps data in a zlib stream made of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK_SIZE: usize = u16::MAX as usize;
+
+    let block_count = data.len().div_ceil(MAX_BLOCK_SIZE).max(1);
+    let mut stream = Vec::with_capacity(data.len() + block_count * 5 + 6);
+
+    // deflate with a 32K window and no preset dictionary, the check bits make it a multiple of 31
+    stream.extend([0x78, 0x01]);
+
+    let mut blocks = data.chunks(MAX_BLOCK_SIZE).peekable();
+    if blocks.peek().is_none() {
+        stream.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let len = block.len() as u16;
+
+        stream.push(is_final as u8);
+        stream.extend(len.to_le_bytes());
+        stream.extend((!len).to_le_bytes());
+        stream.extend(block);
+    }
+
+    stream.extend(adler32(data).to_be_bytes());
+    stream
+}
+
+fn crc32<'a>(data: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let mut crc = !0u32;
+
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % MOD_ADLER;
+        b = (b + a) % MOD_ADLER;
+    }
+
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums_match_known_values() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(adler32(b""), 1);
+    }
+
+    #[test]
+    fn writes_a_png_signature_and_header() {
+        let mut png = Vec::new();
+        encode_png(&Image::filled(3, 2, Color::RED), &mut png).unwrap();
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[8..16], b"\0\0\0\x0dIHDR");
+        assert_eq!(&png[16..29], &[0, 0, 0, 3, 0, 0, 0, 2, 8, 6, 0, 0, 0]);
+        assert_eq!(png[29..33], crc32(&png[12..29]).to_be_bytes());
+        assert_eq!(&png[png.len() - 12..], b"\0\0\0\0IEND\xae\x42\x60\x82");
+    }
+
+    #[test]
+    fn zlib_stream_is_stored_blocks() {
+        let data = vec![7; u16::MAX as usize + 1];
+        let stream = zlib_stored(&data);
+
+        assert_eq!(u16::from_be_bytes([stream[0], stream[1]]) % 31, 0);
+        // a full block that isn't final, then a final block with the last byte
+        assert_eq!(&stream[2..7], &[0, 0xff, 0xff, 0, 0]);
+        assert_eq!(
+            &stream[stream.len() - 10..stream.len() - 4],
+            &[1, 1, 0, 0xfe, 0xff, 7]
+        );
+        assert_eq!(stream[stream.len() - 4..], adler32(&data).to_be_bytes());
+    }
+
+    #[test]
+    fn ppm_round_trips() {
+        let pixels = vec![
+            Color::RED,
+            Color::GREEN,
+            Color::BLUE,
+            Color::WHITE,
+            Color::BLACK,
+            Color::YELLOW,
+        ];
+        let image = Image::from_pixels(3, 2, pixels);
+
+        let mut ppm = Vec::new();
+        encode_ppm(&image, &mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n3 2\n255\n"));
+
+        let decoded = decode_ppm(&ppm).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (3, 2));
+        assert_eq!(decoded.pixels(), image.pixels());
+    }
+
+    #[test]
+    fn decodes_ppm_comments() {
+        let decoded = decode_ppm(b"P6 # a comment\n1 # another\n1 255\n\x01\x02\x03").unwrap();
+        assert_eq!(decoded.pixels(), &[Color::rgb(1, 2, 3)]);
+    }
+
+    #[test]
+    fn rejects_invalid_ppms() {
+        let invalid: [&[u8]; 5] = [
+            b"P3\n1 1\n255\n\0\0\0",
+            b"P6\n1 1\n65535\n\0\0\0",
+            b"P6\n2 1\n255\n\0\0\0",
+            b"P6\n1 1",
+            b"P6\n4294967295 4294967295\n255\n\0\0\0",
+        ];
+
+        for data in invalid {
+            let err = decode_ppm(data).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::{error, fmt, io};
+
+/// Render Error
+pub struct Error {
+    kind: ErrorKind,
+}
+
+impl Error {
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Render Error: {}!", self.kind)
+    }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Render Error: {}!", self.kind)
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(value: ErrorKind) -> Self {
+        Self { kind: value }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        ErrorKind::Io(value).into()
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Types of Render Errors
+pub enum ErrorKind {
+    /// An atlas file could not be read
+    Io(io::Error),
+    /// The atlas isn't valid JSON, holds the byte offset parsing failed at
+    MalformedJson(usize),
+    /// A field of the atlas is missing or has the wrong type, holds the field's name
+    InvalidAtlasField(&'static str),
+    /// No clip with the name exists in the sprite sheet
+    UnknownClip(String),
+    /// A clip refers to a frame the sprite sheet doesn't have, holds the frame's index
+    UnknownFrame(usize),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::Io(err) => write!(f, "failed to read atlas file ({err})"),
+            ErrorKind::MalformedJson(offset) => {
+                write!(f, "atlas is not valid JSON at byte {offset}")
+            }
+            ErrorKind::InvalidAtlasField(field) => {
+                write!(f, "atlas field `{field}` is missing or has the wrong type")
+            }
+            ErrorKind::UnknownClip(name) => write!(f, "unknown animation clip `{name}`"),
+            ErrorKind::UnknownFrame(index) => {
+                write!(f, "frame {index} is not in the sprite sheet")
+            }
+        }
+    }
+}
@@ -0,0 +1,425 @@
+use std::{
+    fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use super::{camera::Bounds, encode, font, Color, Rect, Transform};
+
+/// An RGBA image, used both for sprites and as the software renderer's framebuffer
+#[derive(Clone)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+    /// the part of the image drawing functions draw to, the whole image if None
+    clip: Option<Rect>,
+}
+
+impl Image {
+    /// Creates a transparent image
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::filled(width, height, Color::TRANSPARENT)
+    }
+
+    pub fn filled(width: u32, height: u32, color: Color) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![color; width as usize * height as usize],
+            clip: None,
+        }
+    }
+
+    /// Creates an image from its pixels, row by row from the top left
+    ///
+    /// # Panics
+    ///
+    /// Panics if there isn't exactly one pixel for every position in the image
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<Color>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize,
+            "Image pixels do not match its size!"
+        );
+
+        Self {
+            width,
+            height,
+            pixels,
+            clip: None,
+        }
+    }
+
+    /// Loads a binary (P6) PPM image
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or isn't a binary PPM with 8 bit channels
+    pub fn load_ppm<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        encode::decode_ppm(&fs::read(path)?)
+    }
+
+    /// Saves the image as a binary (P6) PPM, dropping its alpha channel
+    pub fn save_ppm<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        self.write_ppm(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn write_ppm<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        encode::encode_ppm(self, writer)
+    }
+
+    /// Saves the image as an uncompressed RGBA PNG
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        self.write_png(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn write_png<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        encode::encode_png(self, writer)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The image's pixels, row by row from the top left
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [Color] {
+        &mut self.pixels
+    }
+
+    /// Gets a pixel, or None if it lies outside the image
+    pub fn get_pixel(&self, x: i32, y: i32) -> Option<Color> {
+        self.index(x, y).map(|index| self.pixels[index])
+    }
+
+    /// Replaces a pixel, pixels outside the image are ignored
+    pub fn set_pixel(&mut self, x: i32, y: i32, color: Color) {
+        if let Some(index) = self.index(x, y) {
+            self.pixels[index] = color;
+        }
+    }
+
+    /// Limits the drawing functions to part of the image, get_pixel() and set_pixel() ignore it
+    pub fn set_clip_rect(&mut self, clip: Option<Rect>) {
+        self.clip = clip;
+    }
+
+    pub fn clip_rect(&self) -> Option<Rect> {
+        self.clip
+    }
+
+    /// Blends a color over a pixel, pixels outside the image or clip rect are ignored
+    pub fn blend_pixel(&mut self, x: i32, y: i32, color: Color) {
+        let (min_x, min_y, max_x, max_y) = self.draw_area();
+        if x < min_x || y < min_y || x >= max_x || y >= max_y {
+            return;
+        }
+
+        if let Some(index) = self.index(x, y) {
+            self.pixels[index] = color.blend_over(self.pixels[index]);
+        }
+    }
+
+    /// Replaces every pixel
+    pub fn clear(&mut self, color: Color) {
+        self.pixels.fill(color);
+    }
+
+    /// Blends a filled rectangle with its top left corner at x, y
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: u32, height: u32, color: Color) {
+        let (min_x, min_y, max_x, max_y) = self.draw_area();
+        let x_range = x.max(min_x)..(x as i64 + width as i64).min(max_x as i64) as i32;
+        let y_range = y.max(min_y)..(y as i64 + height as i64).min(max_y as i64) as i32;
+
+        for y in y_range {
+            for x in x_range.clone() {
+                self.blend_pixel(x, y, color);
+            }
+        }
+    }
+
+    /// Blends a one pixel wide line, including both end points
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+        let (min_x, min_y, max_x, max_y) = self.draw_area();
+
+        // step along the longer axis so the line has no gaps
+        if (x1 as i64 - x0 as i64).abs() >= (y1 as i64 - y0 as i64).abs() {
+            self.draw_line_steps((x0, y0), (x1, y1), (min_x, max_x), color, |x, y| (x, y));
+        } else {
+            self.draw_line_steps((y0, x0), (y1, x1), (min_y, max_y), color, |y, x| (x, y));
+        }
+    }
+
+    /// Blends text in the built-in 3x5 font with its top left corner at x, y
+    ///
+    /// Every font pixel is drawn as a scale by scale square, new lines start a new row of text
+    pub fn draw_text(&mut self, x: i32, y: i32, text: &str, scale: u32, color: Color) {
+        let advance_x = ((font::GLYPH_WIDTH + 1) * scale) as i32;
+        let advance_y = ((font::GLYPH_HEIGHT + 1) * scale) as i32;
+
+        for (row, line) in text.lines().enumerate() {
+            let line_y = y + row as i32 * advance_y;
+
+            for (column, c) in line.chars().enumerate() {
+                let glyph_x = x + column as i32 * advance_x;
+
+                for (glyph_y, bits) in font::glyph(c).into_iter().enumerate() {
+                    for bit in 0..font::GLYPH_WIDTH {
+                        if bits & (1 << (font::GLYPH_WIDTH - 1 - bit)) != 0 {
+                            self.fill_rect(
+                                glyph_x + (bit * scale) as i32,
+                                line_y + (glyph_y as u32 * scale) as i32,
+                                scale,
+                                scale,
+                                color,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Blends part of another image centered on a transform, multiplying its pixels with tint
+    ///
+    /// Pixels are sampled from the nearest source pixel
+    pub fn draw_image(
+        &mut self,
+        image: &Image,
+        source: Rect,
+        transform: &Transform,
+        tint: Color,
+        flip: (bool, bool),
+    ) {
+        self.draw_transformed(
+            transform,
+            source.width as f32,
+            source.height as f32,
+            |u, v| {
+                let u = if flip.0 { source.width - 1 - u } else { u };
+                let v = if flip.1 { source.height - 1 - v } else { v };
+
+                image
+                    .get_pixel((source.x + u) as i32, (source.y + v) as i32)
+                    .map(|color| color.tinted(tint))
+            },
+        );
+    }
+
+    /// Blends a filled rectangle centered on a transform
+    pub fn fill_transformed_rect(
+        &mut self,
+        transform: &Transform,
+        width: f32,
+        height: f32,
+        color: Color,
+    ) {
+        self.draw_transformed(transform, width, height, |_, _| Some(color));
+    }
+
+    /// Blends a width by height rectangle centered on a transform, sampling the color at every
+    /// pixel position inside of the rectangle
+    fn draw_transformed(
+        &mut self,
+        transform: &Transform,
+        width: f32,
+        height: f32,
+        sample: impl Fn(u32, u32) -> Option<Color>,
+    ) {
+        let (half_width, half_height) = (width / 2.0, height / 2.0);
+
+        // the bounding box of the transformed rectangle, clipped to the draw area
+        let bounds = Bounds::of_local_rect(
+            transform,
+            -half_width,
+            -half_height,
+            half_width,
+            half_height,
+        );
+        let (min_x, min_y, max_x, max_y) = self.draw_area();
+
+        let x_range =
+            (bounds.min_x.floor() as i32).max(min_x)..(bounds.max_x.ceil() as i32).min(max_x);
+        let y_range =
+            (bounds.min_y.floor() as i32).max(min_y)..(bounds.max_y.ceil() as i32).min(max_y);
+
+        for y in y_range {
+            for x in x_range.clone() {
+                // sample at the center of the pixel
+                let Some((u, v)) =
+                    transform.inverse_transform_point(x as f32 + 0.5, y as f32 + 0.5)
+                else {
+                    return;
+                };
+                let (u, v) = (u + half_width, v + half_height);
+
+                if u < 0.0 || v < 0.0 || u >= width || v >= height {
+                    continue;
+                }
+
+                if let Some(color) = sample(u as u32, v as u32) {
+                    self.blend_pixel(x, y, color);
+                }
+            }
+        }
+    }
+
+    /// The pixels drawing functions draw to as min x, min y, max x and max y, the max is exclusive
+    fn draw_area(&self) -> (i32, i32, i32, i32) {
+        let (width, height) = (self.width as i32, self.height as i32);
+        // u32 sums are done in u64 so clip rects reaching past u32::MAX don't wrap
+        let limit =
+            |start: u32, size: u32, max: i32| (start as u64 + size as u64).min(max as u64) as i32;
+
+        match self.clip {
+            Some(clip) => (
+                limit(clip.x, 0, width),
+                limit(clip.y, 0, height),
+                limit(clip.x, clip.width, width),
+                limit(clip.y, clip.height, height),
+            ),
+            None => (0, 0, width, height),
+        }
+    }
+
+    /// Blends a line given as (a, b) points, taking one pixel step along the a axis at a time
+    ///
+    /// The line is clipped to min..max on the a axis before stepping, so lines reaching far
+    /// outside the image cost no more than ones inside it
+    fn draw_line_steps(
+        &mut self,
+        start: (i32, i32),
+        end: (i32, i32),
+        (min, max): (i32, i32),
+        color: Color,
+        to_xy: impl Fn(i32, i32) -> (i32, i32),
+    ) {
+        let ((a0, b0), (a1, b1)) = if start.0 <= end.0 {
+            (start, end)
+        } else {
+            (end, start)
+        };
+        // i128 since the product of two i32 deltas doesn't fit in an i64
+        let (da, db) = (a1 as i128 - a0 as i128, b1 as i128 - b0 as i128);
+
+        for a in a0.max(min)..=a1.min(max - 1) {
+            let b = match da {
+                0 => b0,
+                // rounds to the nearest pixel, which lies between b0 and b1 and so fits in an i32
+                _ => {
+                    (b0 as i128 + (2 * (a as i128 - a0 as i128) * db + da).div_euclid(2 * da))
+                        as i32
+                }
+            };
+
+            let (x, y) = to_xy(a, b);
+            self.blend_pixel(x, y, color);
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return None;
+        }
+
+        Some(y as usize * self.width as usize + x as usize)
+    }
+}
+
+// the clip rect is drawing state, two images are equal if their pixels are
+impl PartialEq for Image {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && self.height == other.height && self.pixels == other.pixels
+    }
+}
+
+impl Eq for Image {}
+
+impl std::fmt::Debug for Image {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Image")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The image with one character per pixel, '#' for drawn pixels and '.' for the rest
+    fn pixels(image: &Image) -> String {
+        let rows = image.pixels().chunks(image.width() as usize).map(|row| {
+            row.iter()
+                .map(|pixel| if *pixel == Color::BLACK { '.' } else { '#' })
+                .collect::<String>()
+        });
+
+        rows.collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn draws_lines_between_their_end_points() {
+        let mut image = Image::filled(5, 3, Color::BLACK);
+        image.draw_line(4, 2, 0, 0, Color::WHITE);
+        assert_eq!(pixels(&image), "#....\n.##..\n...##");
+
+        let mut image = Image::filled(3, 3, Color::BLACK);
+        image.draw_line(1, 0, 1, 2, Color::WHITE);
+        image.draw_line(2, 2, 2, 2, Color::WHITE);
+        assert_eq!(pixels(&image), ".#.\n.#.\n.##");
+    }
+
+    #[test]
+    fn clips_lines_reaching_off_screen() {
+        let mut image = Image::filled(4, 4, Color::BLACK);
+        image.draw_line(-4, -4, 8, 8, Color::WHITE);
+        image.draw_line(-10, 100, 10, 100, Color::WHITE);
+        assert_eq!(pixels(&image), "#...\n.#..\n..#.\n...#");
+
+        // would take billions of steps without clipping, and overflows an i32 delta
+        let mut image = Image::filled(3, 3, Color::BLACK);
+        image.draw_line(i32::MIN, 1, i32::MAX, 1, Color::WHITE);
+        image.draw_line(1, i32::MAX, 1, i32::MIN, Color::WHITE);
+        image.draw_line(i32::MIN, i32::MIN, i32::MAX, i32::MAX, Color::WHITE);
+        assert_eq!(pixels(&image), "##.\n###\n.##");
+    }
+
+    #[test]
+    fn fills_rects_with_huge_coordinates() {
+        let mut image = Image::filled(3, 2, Color::BLACK);
+        image.fill_rect(i32::MAX, 0, u32::MAX, 2, Color::WHITE);
+        image.fill_rect(i32::MIN, -5, 3, 5, Color::WHITE);
+        assert_eq!(pixels(&image), "...\n...");
+
+        image.fill_rect(2, 1, u32::MAX, u32::MAX, Color::WHITE);
+        image.fill_rect(i32::MIN, 0, (1 << 31) + 2, 1, Color::WHITE);
+        assert_eq!(pixels(&image), "##.\n..#");
+    }
+
+    #[test]
+    fn clip_rects_reaching_past_u32_max() {
+        let clip = Rect::new(1, 1, u32::MAX, u32::MAX);
+        assert!(clip.contains(1.0, 1.0));
+        assert!(clip.contains(1e9, 5.0));
+        assert!(!clip.contains(0.5, 5.0));
+
+        let mut image = Image::filled(3, 3, Color::BLACK);
+        image.set_clip_rect(Some(clip));
+        image.fill_rect(0, 0, 3, 3, Color::WHITE);
+        assert_eq!(pixels(&image), "...\n.##\n.##");
+    }
+}
@@ -0,0 +1,309 @@
+//! A small JSON parser for reading sprite sheet atlases
+
+/// A parsed JSON value, objects keep the order of their fields
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parses a JSON document, returning the byte offset of the error if it isn't valid
+    pub fn parse(s: &str) -> Result<Json, usize> {
+        let mut parser = Parser {
+            s,
+            position: 0,
+            depth: 0,
+        };
+
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+
+        match parser.position == s.len() {
+            true => Ok(value),
+            false => Err(parser.position),
+        }
+    }
+
+    /// Gets a field of an object
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        self.as_object()?
+            .iter()
+            .find(|(field, _)| field == key)
+            .map(|(_, value)| value)
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    /// Gets a number that is a whole number that fits in a u32
+    pub fn as_u32(&self) -> Option<u32> {
+        self.as_f64()
+            .filter(|number| number.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(number))
+            .map(|number| number as u32)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(String, Json)]> {
+        match self {
+            Json::Object(fields) => Some(fields),
+            _ => None,
+        }
+    }
+}
+
+/// How deeply arrays and objects can be nested, so malicious documents can't overflow the stack
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    s: &'a str,
+    position: usize,
+    /// how many arrays and objects the parser is inside of
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.s.as_bytes().get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    /// Skips whitespace and consumes the byte if it comes next
+    fn consume(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+
+        let is_next = self.peek() == Some(byte);
+        if is_next {
+            self.position += 1;
+        }
+        is_next
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), usize> {
+        match self.consume(byte) {
+            true => Ok(()),
+            false => Err(self.position),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Json, usize> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some(b'{' | b'[') if self.depth == MAX_DEPTH => Err(self.position),
+            Some(b'{') => self.nested(Self::parse_object),
+            Some(b'[') => self.nested(Self::parse_array),
+            Some(b'"') => self.parse_string().map(Json::String),
+            Some(b't') => self.parse_literal("true", Json::Bool(true)),
+            Some(b'f') => self.parse_literal("false", Json::Bool(false)),
+            Some(b'n') => self.parse_literal("null", Json::Null),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            _ => Err(self.position),
+        }
+    }
+
+    /// Parses an array or object one level deeper
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Json, usize>) -> Result<Json, usize> {
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+
+        value
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: Json) -> Result<Json, usize> {
+        match self.s[self.position..].starts_with(literal) {
+            true => {
+                self.position += literal.len();
+                Ok(value)
+            }
+            false => Err(self.position),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Json, usize> {
+        let start = self.position;
+
+        while self
+            .peek()
+            .is_some_and(|byte| matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9'))
+        {
+            self.position += 1;
+        }
+
+        self.s[start..self.position]
+            .parse()
+            .map(Json::Number)
+            .map_err(|_| start)
+    }
+
+    fn parse_string(&mut self) -> Result<String, usize> {
+        self.expect(b'"')?;
+
+        let mut string = String::new();
+        let mut chars = self.s[self.position..].char_indices();
+
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.position += offset + 1;
+                    return Ok(string);
+                }
+                '\\' => {
+                    let escaped = match chars.next() {
+                        Some((_, '"')) => '"',
+                        Some((_, '\\')) => '\\',
+                        Some((_, '/')) => '/',
+                        Some((_, 'b')) => '\u{8}',
+                        Some((_, 'f')) => '\u{c}',
+                        Some((_, 'n')) => '\n',
+                        Some((_, 'r')) => '\r',
+                        Some((_, 't')) => '\t',
+                        Some((_, 'u')) => {
+                            let hex = (0..4).filter_map(|_| chars.next()).map(|(_, c)| c);
+                            u32::from_str_radix(&hex.collect::<String>(), 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                // surrogate pairs aren't needed for atlases
+                                .unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        _ => return Err(self.position + offset),
+                    };
+                    string.push(escaped);
+                }
+                c => string.push(c),
+            }
+        }
+
+        Err(self.s.len())
+    }
+
+    fn parse_array(&mut self) -> Result<Json, usize> {
+        self.expect(b'[')?;
+
+        let mut values = Vec::new();
+        if self.consume(b']') {
+            return Ok(Json::Array(values));
+        }
+
+        loop {
+            values.push(self.parse_value()?);
+
+            if self.consume(b']') {
+                return Ok(Json::Array(values));
+            }
+            self.expect(b',')?;
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Json, usize> {
+        self.expect(b'{')?;
+
+        let mut fields = Vec::new();
+        if self.consume(b'}') {
+            return Ok(Json::Object(fields));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.expect(b':')?;
+            fields.push((key, self.parse_value()?));
+
+            if self.consume(b'}') {
+                return Ok(Json::Object(fields));
+            }
+            self.expect(b',')?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_values() {
+        let json = Json::parse(r#" { "a": [1, -2.5e1, true, null], "b": { "c": "d" } } "#);
+
+        let expected = Json::Object(vec![
+            (
+                "a".to_owned(),
+                Json::Array(vec![
+                    Json::Number(1.0),
+                    Json::Number(-25.0),
+                    Json::Bool(true),
+                    Json::Null,
+                ]),
+            ),
+            (
+                "b".to_owned(),
+                Json::Object(vec![("c".to_owned(), Json::String("d".to_owned()))]),
+            ),
+        ]);
+        assert_eq!(json, Ok(expected));
+    }
+
+    #[test]
+    fn parses_string_escapes() {
+        let json = Json::parse(r#""a\"b\\c\/\néA""#);
+        assert_eq!(json, Ok(Json::String("a\"b\\c/\néA".to_owned())));
+    }
+
+    #[test]
+    fn reads_fields() {
+        let json = Json::parse(r#"{ "w": 16, "x": 1.5, "y": -1, "name": "walk" }"#).unwrap();
+
+        assert_eq!(json.get("w").and_then(Json::as_u32), Some(16));
+        assert_eq!(json.get("x").and_then(Json::as_u32), None);
+        assert_eq!(json.get("y").and_then(Json::as_u32), None);
+        assert_eq!(json.get("name").and_then(Json::as_str), Some("walk"));
+        assert_eq!(json.get("missing"), None);
+    }
+
+    #[test]
+    fn returns_the_offset_of_errors() {
+        assert_eq!(Json::parse(""), Err(0));
+        assert_eq!(Json::parse("[1, 2"), Err(5));
+        assert_eq!(Json::parse("[1 2]"), Err(3));
+        assert_eq!(Json::parse(r#"{ "a" 1 }"#), Err(6));
+        assert_eq!(Json::parse("tru"), Err(0));
+        assert_eq!(Json::parse(r#""abc"#), Err(4));
+        assert_eq!(Json::parse("1 2"), Err(2));
+    }
+
+    #[test]
+    fn limits_nesting() {
+        let nested = |depth| "[".repeat(depth) + &"]".repeat(depth);
+
+        assert!(Json::parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(Json::parse(&nested(MAX_DEPTH + 1)), Err(MAX_DEPTH));
+        assert_eq!(Json::parse(&nested(100_000)), Err(MAX_DEPTH));
+    }
+}
@@ -0,0 +1,420 @@
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    ecs::{self, UnsafeComponentCell},
+    scene::SceneState,
+    Engine, Entity, Scene,
+};
+
+mod animation;
+mod camera;
+mod color;
+mod component;
+mod draw_list;
+mod encode;
+mod err;
+mod font;
+mod image;
+mod json;
+mod sheet;
+mod terminal;
+
+pub use animation::{Animation, AnimationSystem};
+use camera::Bounds;
+pub use camera::Camera;
+pub use color::Color;
+pub use component::{Glyph, Parent, Rect, RenderLayer, Shape, Sprite, Transform};
+use draw_list::{Draw, DrawList};
+pub use err::{Error, ErrorKind};
+pub use image::Image;
+pub use sheet::{AnimationClip, AnimationFrame, LoopMode, SpriteSheet};
+pub use terminal::TerminalRenderer;
+
+/// Draws the active scenes once every frame, after their systems have run
+///
+/// The engine calls begin_frame(), then render_scene() for the outgoing scene of a transition
+/// and the current scene, and then end_frame(). Platforms show the result in Platform::present()
+pub trait Renderer: Send {
+    fn begin_frame(&mut self, _engine: &Engine) {}
+
+    /// Draws the entities of a scene
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the scene or one of its components can't be accessed
+    fn render_scene(&mut self, engine: &Engine, scene: &Scene) -> Result<(), ecs::Error>;
+
+    /// Finishes the frame, such as by writing it out
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frame couldn't be written
+    fn end_frame(&mut self, _engine: &Engine) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Draws every entity with a Transform and a Sprite or Shape into an in-memory framebuffer
+///
+/// Entities are drawn in order of their RenderLayer and then their ids, once for every
+/// Camera they are visible to. The framebuffer is shared so it can be read by the platform
+/// or saved after the engine has stopped
+pub struct SoftwareRenderer {
+    framebuffer: Arc<Mutex<Image>>,
+    clear_color: Color,
+}
+
+impl SoftwareRenderer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            framebuffer: Arc::new(Mutex::new(Image::new(width, height))),
+            clear_color: Color::BLACK,
+        }
+    }
+
+    /// The color the framebuffer is cleared to at the start of every frame
+    pub fn with_clear_color(mut self, clear_color: Color) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    /// A handle to the framebuffer, which holds the last frame once rendering has finished
+    pub fn framebuffer(&self) -> Arc<Mutex<Image>> {
+        Arc::clone(&self.framebuffer)
+    }
+
+    /// Adds the draws of an entity's sprite and shape, unless the camera can't see them
+    fn push_draws(
+        draws: &mut DrawList,
+        state: &SceneState,
+        drawable: &Drawable,
+        camera: Option<&Camera>,
+    ) -> Result<(), ecs::Error> {
+        let Drawable {
+            entity,
+            world,
+            layer,
+        } = drawable;
+
+        let screen = camera.map_or(*world, |camera| camera.world_to_screen_transform(world));
+        let is_visible = |bounds: Bounds| {
+            camera.is_none_or(|camera| camera.visible_bounds().intersects(&bounds))
+        };
+
+        if let Some(sprite) = get_optional_component::<Sprite>(state, entity)? {
+            let source = sprite.source_rect();
+            let (half_width, half_height) = (source.width as f32 / 2.0, source.height as f32 / 2.0);
+            let bounds =
+                Bounds::of_local_rect(world, -half_width, -half_height, half_width, half_height);
+
+            if is_visible(bounds) {
+                let sprite = sprite.clone();
+                draws.push(*layer, Draw::Sprite { sprite, screen });
+            }
+        }
+
+        if let Some(shape) = get_optional_component::<Shape>(state, entity)? {
+            let bounds = match &*shape {
+                Shape::Rect { width, height, .. } => Bounds::of_local_rect(
+                    world,
+                    -width / 2.0,
+                    -height / 2.0,
+                    width / 2.0,
+                    height / 2.0,
+                ),
+                Shape::Line { dx, dy, .. } => {
+                    Bounds::of_local_rect(world, dx.min(0.0), dy.min(0.0), dx.max(0.0), dy.max(0.0))
+                }
+                // text is drawn on screen at a whole scale and never rotated, see draw_text()
+                Shape::Text { text, .. } => {
+                    let scale = screen.scale_x.round().max(1.0);
+                    let columns = text.lines().map(|line| line.chars().count()).max();
+                    let width = columns.unwrap_or(0) as f32 * (font::GLYPH_WIDTH + 1) as f32;
+                    let height = text.lines().count() as f32 * (font::GLYPH_HEIGHT + 1) as f32;
+
+                    let (x, y) = (screen.x.round(), screen.y.round());
+                    let (max_x, max_y) = (x + width * scale, y + height * scale);
+                    let ((min_x, min_y), (max_x, max_y)) = match camera {
+                        Some(camera) => (
+                            camera.screen_to_world(x, y),
+                            camera.screen_to_world(max_x, max_y),
+                        ),
+                        None => ((x, y), (max_x, max_y)),
+                    };
+
+                    Bounds {
+                        min_x,
+                        min_y,
+                        max_x,
+                        max_y,
+                    }
+                }
+            };
+
+            if is_visible(bounds) {
+                let shape = shape.clone();
+                draws.push(*layer, Draw::Shape { shape, screen });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    fn begin_frame(&mut self, _engine: &Engine) {
+        self.framebuffer.lock().unwrap().clear(self.clear_color);
+    }
+
+    fn render_scene(&mut self, engine: &Engine, scene: &Scene) -> Result<(), ecs::Error> {
+        let state = engine.scenes().get_scene(scene)?;
+        let drawables = drawables(&state)?;
+
+        let mut framebuffer = self.framebuffer.lock().unwrap();
+        for camera in views(&state)?.iter() {
+            let mut draws = DrawList::default();
+            for drawable in drawables.iter() {
+                Self::push_draws(&mut draws, &state, drawable, camera.as_ref())?;
+            }
+
+            framebuffer.set_clip_rect(camera.map(|camera| camera.viewport));
+            let draw_count = draws.len();
+            draws.draw(&mut framebuffer);
+            framebuffer.set_clip_rect(None);
+
+            engine.logger().trace(
+                "render",
+                format_args!("drew scene {:?} with {draw_count} draws", scene),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// The cameras of a scene in order of their entity ids, or a single None if it has no cameras
+fn views(state: &SceneState) -> Result<Vec<Option<Camera>>, ecs::Error> {
+    let mut entities = state.get_living_entities();
+    entities.sort_by_key(Entity::id);
+
+    let mut cameras = Vec::new();
+    for entity in entities.iter() {
+        if let Some(camera) = get_optional_component::<Camera>(state, entity)? {
+            cameras.push(Some(*camera));
+        }
+    }
+
+    if cameras.is_empty() {
+        cameras.push(None);
+    }
+
+    Ok(cameras)
+}
+
+/// How many parents are followed before the rest of a hierarchy is ignored, such as in a cycle
+const MAX_HIERARCHY_DEPTH: usize = 64;
+
+/// An entity with a Transform, which may be drawn
+struct Drawable {
+    entity: Entity,
+    /// the entity's transform in the world
+    world: Transform,
+    layer: RenderLayer,
+}
+
+/// Every entity with a Transform, in the order they are drawn in
+///
+/// Entities are sorted by their render layer and then their id, so they are drawn in the
+/// same order every frame no matter how the scene stores them
+fn drawables(state: &SceneState) -> Result<Vec<Drawable>, ecs::Error> {
+    let mut drawables = Vec::new();
+
+    for entity in state.get_living_entities() {
+        let Some(world) = world_transform(state, &entity)? else {
+            continue;
+        };
+        let layer = get_optional_component::<RenderLayer>(state, &entity)?
+            .map_or(RenderLayer::default(), |layer| *layer);
+
+        drawables.push(Drawable {
+            entity,
+            world,
+            layer,
+        });
+    }
+
+    drawables.sort_by_key(|drawable| (drawable.layer, drawable.entity.id()));
+    Ok(drawables)
+}
+
+/// Combines an entity's Transform with those of its parents, or None if it has no Transform
+///
+/// The hierarchy ends at a parent without a Transform or that no longer exists
+fn world_transform(state: &SceneState, entity: &Entity) -> Result<Option<Transform>, ecs::Error> {
+    let Some(transform) = get_optional_component::<Transform>(state, entity)? else {
+        return Ok(None);
+    };
+
+    let mut world = *transform;
+    let mut child = entity.clone();
+
+    for _ in 0..MAX_HIERARCHY_DEPTH {
+        let Some(parent) = get_optional_component::<Parent>(state, &child)? else {
+            break;
+        };
+        let parent = parent.0.clone();
+
+        match get_optional_component::<Transform>(state, &parent) {
+            Ok(Some(parent_transform)) => world = world.combined_with(&parent_transform),
+            Ok(None) => break,
+            Err(err) if err.kind() == ecs::ErrorKind::EntityDoesNotExist => break,
+            Err(err) => return Err(err),
+        }
+
+        child = parent;
+    }
+
+    Ok(Some(world))
+}
+
+/// Gets a component, or None if the entity doesn't have it or the scene never registered it
+fn get_optional_component<'a, C: Send + 'static>(
+    state: &'a SceneState,
+    entity: &Entity,
+) -> Result<Option<UnsafeComponentCell<'a, C>>, ecs::Error> {
+    match state.get_component::<C>(entity) {
+        Ok(component) => Ok(Some(component)),
+        Err(err)
+            if matches!(
+                err.kind(),
+                ecs::ErrorKind::ComponentNotRegistered | ecs::ErrorKind::EntityDoesNotOwnComponent
+            ) =>
+        {
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Draws a frame of a small scene and returns it with one character per pixel
+    fn render_frame() -> String {
+        let mut engine = Engine::new();
+        let scene = engine.create_scene().unwrap();
+        let state = engine.scenes().get_scene(&scene).unwrap();
+        state.register_component::<Transform>();
+        state.register_component::<Sprite>();
+        state.register_component::<Shape>();
+        state.register_component::<RenderLayer>();
+
+        let red = Arc::new(Image::filled(2, 2, Color::RED));
+        let sprite = state.create_entity().unwrap();
+        state
+            .add_component(&sprite, Transform::new(2.0, 2.0))
+            .unwrap();
+        state.add_component(&sprite, Sprite::new(red)).unwrap();
+
+        let rect = Shape::Rect {
+            width: 3.0,
+            height: 1.0,
+            color: Color::BLUE,
+        };
+        let shape = state.create_entity().unwrap();
+        state
+            .add_component(&shape, Transform::new(3.5, 2.5))
+            .unwrap();
+        state.add_component(&shape, rect).unwrap();
+        state.add_component(&shape, RenderLayer::new(1)).unwrap();
+
+        let line = Shape::Line {
+            dx: 5.0,
+            dy: 0.0,
+            color: Color::GREEN,
+        };
+        let below = state.create_entity().unwrap();
+        state
+            .add_component(&below, Transform::new(0.0, 4.0))
+            .unwrap();
+        state.add_component(&below, line).unwrap();
+        state.add_component(&below, RenderLayer::new(-1)).unwrap();
+
+        let mut renderer = SoftwareRenderer::new(6, 6);
+        renderer.begin_frame(&engine);
+        renderer.render_scene(&engine, &scene).unwrap();
+        renderer.end_frame(&engine).unwrap();
+
+        let framebuffer = renderer.framebuffer();
+        let framebuffer = framebuffer.lock().unwrap();
+        framebuffer
+            .pixels()
+            .chunks(framebuffer.width() as usize)
+            .map(|row| {
+                row.iter()
+                    .map(|&pixel| match pixel {
+                        Color::BLACK => '.',
+                        Color::RED => 'R',
+                        Color::GREEN => 'G',
+                        Color::BLUE => 'B',
+                        _ => '?',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn culls_text_where_it_is_drawn() {
+        let mut engine = Engine::new();
+        let scene = engine.create_scene().unwrap();
+        let state = engine.scenes().get_scene(&scene).unwrap();
+        state.register_component::<Transform>();
+        state.register_component::<Shape>();
+        state.register_component::<Camera>();
+
+        // shows the world from x 12 to 20, to the right of where the text starts
+        let camera = state.create_entity().unwrap();
+        let view = Camera::new(Rect::new(0, 0, 8, 8)).with_position(16.0, 4.0);
+        state.add_component(&camera, view).unwrap();
+
+        // rotating the text's transform doesn't rotate the text, it still runs to the right
+        let text = Shape::Text {
+            text: "AB".to_owned(),
+            color: Color::WHITE,
+        };
+        let label = state.create_entity().unwrap();
+        let transform = Transform::new(0.0, 0.0)
+            .with_rotation(std::f32::consts::PI)
+            .with_scale(2.6, 2.6);
+        state.add_component(&label, transform).unwrap();
+        state.add_component(&label, text).unwrap();
+
+        let mut renderer = SoftwareRenderer::new(8, 8);
+        renderer.begin_frame(&engine);
+        renderer.render_scene(&engine, &scene).unwrap();
+
+        let framebuffer = renderer.framebuffer();
+        let framebuffer = framebuffer.lock().unwrap();
+        assert!(framebuffer.pixels().contains(&Color::WHITE));
+    }
+
+    #[test]
+    fn software_renderer_draws_a_frame() {
+        // the line is on a lower layer and the rect on a higher one than the sprite
+        let expected = "\
+......
+.RR...
+.RBBB.
+......
+GGGGGG
+......";
+
+        assert_eq!(render_frame(), expected);
+    }
+}
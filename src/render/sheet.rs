@@ -0,0 +1,370 @@
+use std::{collections::HashMap, fs, path::Path, sync::Arc, time::Duration};
+
+use super::{json::Json, Error, ErrorKind, Image, Rect};
+
+/// How long atlas frames without a duration are shown
+const DEFAULT_FRAME_DURATION: Duration = Duration::from_millis(100);
+
+/// What an animation does once it reaches the end of its clip
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LoopMode {
+    /// Stops on the last frame
+    Once,
+    /// Starts over from the first frame
+    #[default]
+    Loop,
+    /// Plays backwards to the first frame and then forwards again
+    PingPong,
+}
+
+/// A frame of a clip
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationFrame {
+    /// The index of the frame in the sprite sheet
+    pub index: usize,
+    pub duration: Duration,
+    /// An event fired whenever the animation reaches this frame
+    pub event: Option<String>,
+}
+
+/// A named sequence of sprite sheet frames
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnimationClip {
+    pub frames: Vec<AnimationFrame>,
+    pub mode: LoopMode,
+}
+
+impl AnimationClip {
+    pub fn new(mode: LoopMode) -> Self {
+        Self {
+            frames: Vec::new(),
+            mode,
+        }
+    }
+
+    /// Adds a sprite sheet frame that is shown for the duration
+    pub fn with_frame(mut self, index: usize, duration: Duration) -> Self {
+        self.frames.push(AnimationFrame {
+            index,
+            duration,
+            event: None,
+        });
+        self
+    }
+
+    /// Fires an event whenever the animation reaches a frame of the clip, frames that don't
+    /// exist are ignored
+    pub fn with_event(mut self, frame: usize, event: &str) -> Self {
+        if let Some(frame) = self.frames.get_mut(frame) {
+            frame.event = Some(event.to_owned());
+        }
+        self
+    }
+
+    /// How long it takes to play the clip from its first to its last frame
+    pub fn duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.duration).sum()
+    }
+}
+
+/// An image split into frames, along with the animation clips made from them
+#[derive(Clone, Debug)]
+pub struct SpriteSheet {
+    image: Arc<Image>,
+    frames: Vec<Rect>,
+    /// the names of frames loaded from an atlas
+    frame_names: HashMap<String, usize>,
+    clips: HashMap<String, AnimationClip>,
+}
+
+impl SpriteSheet {
+    /// Splits an image into frames of the same size, row by row from the top left
+    ///
+    /// Frames that don't fit in the image at its right and bottom edge are left out
+    pub fn from_grid(image: Arc<Image>, frame_width: u32, frame_height: u32) -> Self {
+        let columns = image.width().checked_div(frame_width).unwrap_or(0);
+        let rows = image.height().checked_div(frame_height).unwrap_or(0);
+
+        let frames = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (column, row)))
+            .map(|(column, row)| {
+                Rect::new(
+                    column * frame_width,
+                    row * frame_height,
+                    frame_width,
+                    frame_height,
+                )
+            })
+            .collect();
+
+        Self {
+            image,
+            frames,
+            frame_names: HashMap::new(),
+            clips: HashMap::new(),
+        }
+    }
+
+    /// Reads the frames and clips of an image from a JSON atlas
+    ///
+    /// Atlases use the format exported by Aseprite and TexturePacker. `frames` is either an
+    /// object of named frames or an array of frames with a `filename`, every frame has a
+    /// `frame` rect with `x`, `y`, `w` and `h` and an optional `duration` in milliseconds.
+    /// Every tag in `meta.frameTags` becomes a clip of the frames `from` to `to`, played
+    /// `forward`, in `reverse` or as `pingpong`
+    ///
+    /// ```json
+    /// {
+    ///     "frames": {
+    ///         "walk 0": { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "duration": 100 },
+    ///         "walk 1": { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "duration": 100 }
+    ///     },
+    ///     "meta": {
+    ///         "frameTags": [{ "name": "walk", "from": 0, "to": 1, "direction": "forward" }]
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the atlas isn't valid JSON or a field is missing or of the wrong type
+    pub fn from_atlas(image: Arc<Image>, atlas: &str) -> Result<Self, Error> {
+        let atlas = Json::parse(atlas).map_err(ErrorKind::MalformedJson)?;
+        let invalid = |field| Error::from(ErrorKind::InvalidAtlasField(field));
+
+        // frames are either keyed by name or have a filename field
+        let named_frames: Vec<(String, &Json)> = match atlas.get("frames") {
+            Some(Json::Object(frames)) => frames
+                .iter()
+                .map(|(name, frame)| (name.clone(), frame))
+                .collect(),
+            Some(Json::Array(frames)) => frames
+                .iter()
+                .map(|frame| {
+                    let name = frame.get("filename").and_then(Json::as_str);
+                    Ok((name.ok_or_else(|| invalid("filename"))?.to_owned(), frame))
+                })
+                .collect::<Result<_, Error>>()?,
+            _ => return Err(invalid("frames")),
+        };
+
+        let mut sheet = Self {
+            image,
+            frames: Vec::with_capacity(named_frames.len()),
+            frame_names: HashMap::with_capacity(named_frames.len()),
+            clips: HashMap::new(),
+        };
+        let mut durations = Vec::with_capacity(named_frames.len());
+
+        for (name, frame) in named_frames {
+            let rect = frame.get("frame").ok_or_else(|| invalid("frame"))?;
+            let field = |name| {
+                rect.get(name)
+                    .and_then(Json::as_u32)
+                    .ok_or_else(|| invalid(name))
+            };
+
+            sheet.frame_names.insert(name, sheet.frames.len());
+            sheet.frames.push(Rect::new(
+                field("x")?,
+                field("y")?,
+                field("w")?,
+                field("h")?,
+            ));
+
+            durations.push(match frame.get("duration") {
+                Some(duration) => Duration::from_millis(
+                    duration.as_u32().ok_or_else(|| invalid("duration"))? as u64,
+                ),
+                None => DEFAULT_FRAME_DURATION,
+            });
+        }
+
+        let tags = atlas.get("meta").and_then(|meta| meta.get("frameTags"));
+        for tag in tags.and_then(Json::as_array).unwrap_or_default() {
+            let name = tag.get("name").and_then(Json::as_str);
+            let from = tag.get("from").and_then(Json::as_u32);
+            let to = tag.get("to").and_then(Json::as_u32);
+
+            let name = name.ok_or_else(|| invalid("name"))?;
+            let from = from.ok_or_else(|| invalid("from"))? as usize;
+            let to = to.ok_or_else(|| invalid("to"))? as usize;
+
+            if to >= sheet.frames.len() || from > to {
+                return Err(invalid("to"));
+            }
+
+            let mut indices = (from..=to).collect::<Vec<_>>();
+            let mut mode = LoopMode::Loop;
+            match tag.get("direction").and_then(Json::as_str) {
+                None | Some("forward") => {}
+                Some("reverse") => indices.reverse(),
+                Some("pingpong") => mode = LoopMode::PingPong,
+                Some(_) => return Err(invalid("direction")),
+            }
+
+            let clip = indices
+                .into_iter()
+                .fold(AnimationClip::new(mode), |clip, index| {
+                    clip.with_frame(index, durations[index])
+                });
+            sheet.clips.insert(name.to_owned(), clip);
+        }
+
+        Ok(sheet)
+    }
+
+    /// Loads a JSON atlas for an image from a file, see SpriteSheet::from_atlas()
+    pub fn load_atlas<P: AsRef<Path>>(image: Arc<Image>, path: P) -> Result<Self, Error> {
+        Self::from_atlas(image, &fs::read_to_string(path)?)
+    }
+
+    pub fn image(&self) -> &Arc<Image> {
+        &self.image
+    }
+
+    /// The part of the image a frame covers
+    pub fn frame(&self, index: usize) -> Option<Rect> {
+        self.frames.get(index).copied()
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Finds a frame loaded from an atlas by its name
+    pub fn frame_index(&self, name: &str) -> Option<usize> {
+        self.frame_names.get(name).copied()
+    }
+
+    /// Adds a clip, replacing the clip with the same name
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the clip refers to a frame the sprite sheet doesn't have
+    pub fn add_clip(&mut self, name: &str, clip: AnimationClip) -> Result<(), Error> {
+        if let Some(frame) = clip
+            .frames
+            .iter()
+            .find(|frame| frame.index >= self.frames.len())
+        {
+            return Err(ErrorKind::UnknownFrame(frame.index).into());
+        }
+
+        self.clips.insert(name.to_owned(), clip);
+        Ok(())
+    }
+
+    pub fn clip(&self, name: &str) -> Option<&AnimationClip> {
+        self.clips.get(name)
+    }
+
+    /// Changes a clip, such as to add events to a clip loaded from an atlas
+    pub fn clip_mut(&mut self, name: &str) -> Option<&mut AnimationClip> {
+        self.clips.get_mut(name)
+    }
+
+    /// The names of every clip, in no particular order
+    pub fn clip_names(&self) -> impl Iterator<Item = &str> {
+        self.clips.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(atlas: &str) -> Result<SpriteSheet, Error> {
+        SpriteSheet::from_atlas(Arc::new(Image::new(32, 16)), atlas)
+    }
+
+    #[test]
+    fn loads_named_frames_and_tags() {
+        let sheet = load(
+            r#"{
+                "frames": {
+                    "idle": { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "duration": 250 },
+                    "jump": { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 } }
+                },
+                "meta": {
+                    "frameTags": [
+                        { "name": "bob", "from": 0, "to": 1, "direction": "pingpong" },
+                        { "name": "fall", "from": 0, "to": 1, "direction": "reverse" }
+                    ]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(sheet.frame_count(), 2);
+        assert_eq!(sheet.frame_index("jump"), Some(1));
+        assert_eq!(sheet.frame(1), Some(Rect::new(16, 0, 16, 16)));
+
+        let bob = sheet.clip("bob").unwrap();
+        assert_eq!(bob.mode, LoopMode::PingPong);
+        assert_eq!(bob.frames[0].duration, Duration::from_millis(250));
+        assert_eq!(bob.frames[1].duration, DEFAULT_FRAME_DURATION);
+
+        let fall = sheet.clip("fall").unwrap();
+        let indices = fall.frames.iter().map(|frame| frame.index);
+        assert_eq!(indices.collect::<Vec<_>>(), [1, 0]);
+        assert_eq!(fall.mode, LoopMode::Loop);
+    }
+
+    #[test]
+    fn loads_frame_arrays() {
+        let sheet = load(
+            r#"{ "frames": [
+                { "filename": "a", "frame": { "x": 0, "y": 0, "w": 8, "h": 8 } },
+                { "filename": "b", "frame": { "x": 8, "y": 0, "w": 8, "h": 8 } }
+            ] }"#,
+        )
+        .unwrap();
+
+        assert_eq!(sheet.frame_index("b"), Some(1));
+        assert_eq!(sheet.frame(1), Some(Rect::new(8, 0, 8, 8)));
+        assert_eq!(sheet.clip_names().count(), 0);
+    }
+
+    #[test]
+    fn rejects_invalid_atlases() {
+        let tagged = |tag: &str| {
+            let frame = r#""a": { "frame": { "x": 0, "y": 0, "w": 8, "h": 8 } }"#;
+            format!(r#"{{ "frames": {{ {frame} }}, "meta": {{ "frameTags": [{tag}] }} }}"#)
+        };
+
+        let invalid = [
+            (r#"{ "frames": "#.to_owned(), None),
+            (r#"{ "meta": {} }"#.to_owned(), Some("frames")),
+            (r#"{ "frames": { "a": {} } }"#.to_owned(), Some("frame")),
+            (
+                r#"{ "frames": { "a": { "frame": { "x": 0, "y": 0, "w": -8, "h": 8 } } } }"#
+                    .to_owned(),
+                Some("w"),
+            ),
+            (tagged(r#"{ "name": "t", "from": 0, "to": 1 }"#), Some("to")),
+            (
+                tagged(r#"{ "name": "t", "from": 0, "to": 0, "direction": "up" }"#),
+                Some("direction"),
+            ),
+        ];
+
+        for (atlas, field) in invalid {
+            match (load(&atlas).unwrap_err().kind(), field) {
+                (ErrorKind::MalformedJson(_), None) => {}
+                (ErrorKind::InvalidAtlasField(invalid), Some(field)) => assert_eq!(*invalid, field),
+                (kind, _) => panic!("unexpected error for {atlas}: {kind}"),
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_clips_of_missing_frames() {
+        let mut sheet = SpriteSheet::from_grid(Arc::new(Image::new(32, 16)), 16, 16);
+        assert_eq!(sheet.frame_count(), 2);
+
+        let clip = AnimationClip::new(LoopMode::Once).with_frame(2, DEFAULT_FRAME_DURATION);
+        let err = sheet.add_clip("clip", clip).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UnknownFrame(2)));
+    }
+}
@@ -0,0 +1,301 @@
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+    mem,
+};
+
+use super::{
+    drawables, get_optional_component, views, Camera, Color, Drawable, Glyph, Rect, Renderer, Shape,
+};
+use crate::{ecs, input, scene::SceneState, Engine, Scene};
+
+#[derive(Clone, Copy, PartialEq)]
+struct Cell {
+    ch: char,
+    color: Color,
+    background: Option<Color>,
+}
+
+const EMPTY_CELL: Cell = Cell {
+    ch: ' ',
+    color: Color::WHITE,
+    background: None,
+};
+
+/// Draws entities with a Transform and a Glyph into the terminal with ANSI escape codes
+///
+/// Without a Camera every terminal cell covers cell_size units of the world, starting at 0, 0
+/// in the top left corner. Cameras treat cells as screen pixels, so their viewports are in cells
+/// and their zoom is cells per world unit. Text shapes are drawn one character per cell, other
+/// shapes and sprites are ignored. Entities are drawn in order of their RenderLayer and then
+/// their ids, so higher layers cover lower ones
+///
+/// Frames are double buffered and only the cells that changed since the last frame are
+/// written. The renderer switches to the terminal's alternate screen on the first frame and
+/// switches back when it is dropped
+pub struct TerminalRenderer {
+    columns: u32,
+    rows: u32,
+    cell_size: (f32, f32),
+    /// the cells on screen
+    front: Vec<Cell>,
+    /// the cells of the frame being rendered
+    back: Vec<Cell>,
+    writer: Box<dyn Write + Send>,
+    has_started: bool,
+}
+
+impl TerminalRenderer {
+    /// Creates a renderer that draws columns by rows cells to stdout
+    pub fn new(columns: u32, rows: u32) -> Self {
+        let cell_count = columns as usize * rows as usize;
+
+        Self {
+            columns,
+            rows,
+            cell_size: (1.0, 1.0),
+            front: vec![EMPTY_CELL; cell_count],
+            back: vec![EMPTY_CELL; cell_count],
+            writer: Box::new(io::stdout()),
+            has_started: false,
+        }
+    }
+
+    /// Creates a renderer that fills the terminal stdin belongs to
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if stdin isn't a terminal
+    pub fn fit_terminal() -> io::Result<Self> {
+        let size = input::stty(&["size"])?;
+
+        let mut parts = size.split_whitespace().map(str::parse::<u32>);
+        match (parts.next(), parts.next()) {
+            (Some(Ok(rows)), Some(Ok(columns))) => Ok(Self::new(columns, rows)),
+            _ => Err(io::Error::other("stty printed an invalid terminal size")),
+        }
+    }
+
+    /// Sets how many world units every cell covers, 1 by 1 by default
+    pub fn with_cell_size(mut self, width: f32, height: f32) -> Self {
+        self.cell_size = (width, height);
+        self
+    }
+
+    /// Writes frames somewhere other than stdout, such as a file or a buffer
+    pub fn with_writer<W: Write + Send + 'static>(mut self, writer: W) -> Self {
+        self.writer = Box::new(writer);
+        self
+    }
+
+    pub fn columns(&self) -> u32 {
+        self.columns
+    }
+
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    /// The cell at a screen position, or None if it isn't on screen or outside the viewport
+    fn cell_index(&self, x: f32, y: f32, viewport: Option<Rect>) -> Option<usize> {
+        let (column, row) = (x.floor(), y.floor());
+
+        if column < 0.0 || row < 0.0 || column >= self.columns as f32 || row >= self.rows as f32 {
+            return None;
+        }
+        if viewport.is_some_and(|viewport| !viewport.contains(column, row)) {
+            return None;
+        }
+
+        Some(row as usize * self.columns as usize + column as usize)
+    }
+
+    /// Draws an entity's glyph and text, cells outside the camera's viewport are culled
+    fn draw_entity(
+        &mut self,
+        state: &SceneState,
+        drawable: &Drawable,
+        camera: Option<&Camera>,
+    ) -> Result<(), ecs::Error> {
+        let Drawable { entity, world, .. } = drawable;
+
+        let (x, y) = match camera {
+            Some(camera) => camera.world_to_screen(world.x, world.y),
+            None => (world.x / self.cell_size.0, world.y / self.cell_size.1),
+        };
+        let viewport = camera.map(|camera| camera.viewport);
+
+        if let Some(glyph) = get_optional_component::<Glyph>(state, entity)? {
+            if let Some(index) = self.cell_index(x, y, viewport) {
+                self.back[index] = Cell {
+                    ch: glyph.ch,
+                    color: glyph.color,
+                    background: glyph.background,
+                };
+            }
+        }
+
+        if let Some(shape) = get_optional_component::<Shape>(state, entity)? {
+            if let Shape::Text { text, color } = &*shape {
+                for (row, line) in text.lines().enumerate() {
+                    for (column, ch) in line.chars().enumerate() {
+                        let index = self.cell_index(x + column as f32, y + row as f32, viewport);
+
+                        if let Some(index) = index {
+                            self.back[index] = Cell {
+                                ch,
+                                color: *color,
+                                background: self.back[index].background,
+                            };
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Renderer for TerminalRenderer {
+    fn begin_frame(&mut self, _engine: &Engine) {
+        self.back.fill(EMPTY_CELL);
+    }
+
+    fn render_scene(&mut self, engine: &Engine, scene: &Scene) -> Result<(), ecs::Error> {
+        let state = engine.scenes().get_scene(scene)?;
+        let drawables = drawables(&state)?;
+
+        for camera in views(&state)?.iter() {
+            for drawable in drawables.iter() {
+                self.draw_entity(&state, drawable, camera.as_ref())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn end_frame(&mut self, _engine: &Engine) -> io::Result<()> {
+        let mut output = String::new();
+
+        if !self.has_started {
+            // alternate screen, hide the cursor and clear the screen
+            output.push_str("\x1b[?1049h\x1b[?25l\x1b[2J");
+            // make every cell differ from the screen so the whole frame is drawn
+            self.front.fill(Cell {
+                ch: '\0',
+                ..EMPTY_CELL
+            });
+            self.has_started = true;
+        }
+
+        let mut cursor = None;
+        let mut colors = None;
+
+        for (index, (cell, screen_cell)) in self.back.iter().zip(self.front.iter()).enumerate() {
+            if cell == screen_cell {
+                continue;
+            }
+
+            // control characters would move the cursor or change the terminal's state
+            let ch = match cell.ch.is_control() {
+                true => '?',
+                false => cell.ch,
+            };
+
+            let position = (index % self.columns as usize, index / self.columns as usize);
+            if cursor != Some(position) {
+                let _ = write!(output, "\x1b[{};{}H", position.1 + 1, position.0 + 1);
+            }
+            // the cursor moves one column right after an ASCII character, other characters
+            // can be wide or zero width, so the next cell moves the cursor explicitly
+            cursor = ch.is_ascii().then_some((position.0 + 1, position.1));
+
+            if colors != Some((cell.color, cell.background)) {
+                let Color { r, g, b, .. } = cell.color;
+                let _ = write!(output, "\x1b[38;2;{r};{g};{b}m");
+
+                match cell.background {
+                    Some(Color { r, g, b, .. }) => {
+                        let _ = write!(output, "\x1b[48;2;{r};{g};{b}m");
+                    }
+                    None => output.push_str("\x1b[49m"),
+                }
+                colors = Some((cell.color, cell.background));
+            }
+
+            output.push(ch);
+        }
+
+        output.push_str("\x1b[0m");
+
+        mem::swap(&mut self.front, &mut self.back);
+
+        self.writer.write_all(output.as_bytes())?;
+        self.writer.flush()
+    }
+}
+
+impl Drop for TerminalRenderer {
+    fn drop(&mut self) {
+        if self.has_started {
+            // show the cursor and leave the alternate screen, nothing can be done if it fails
+            let _ = self.writer.write_all(b"\x1b[0m\x1b[?25h\x1b[?1049l");
+            let _ = self.writer.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// A writer tests can read back from after the renderer took it
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Writes a frame with the characters in the first row and returns what was written
+    fn write_frame(renderer: &mut TerminalRenderer, buffer: &SharedBuffer, row: &str) -> String {
+        for (cell, ch) in renderer.back.iter_mut().zip(row.chars()) {
+            cell.ch = ch;
+        }
+        renderer.end_frame(&Engine::new()).unwrap();
+
+        String::from_utf8(mem::take(&mut *buffer.0.lock().unwrap())).unwrap()
+    }
+
+    #[test]
+    fn only_writes_changed_cells() {
+        let buffer = SharedBuffer::default();
+        let mut renderer = TerminalRenderer::new(4, 1).with_writer(buffer.clone());
+
+        write_frame(&mut renderer, &buffer, "ab  ");
+        renderer.back.copy_from_slice(&renderer.front);
+        let output = write_frame(&mut renderer, &buffer, "ac  ");
+
+        assert_eq!(output, "\x1b[1;2H\x1b[38;2;255;255;255m\x1b[49mc\x1b[0m");
+    }
+
+    #[test]
+    fn moves_the_cursor_after_non_ascii_characters() {
+        let buffer = SharedBuffer::default();
+        let mut renderer = TerminalRenderer::new(4, 1).with_writer(buffer.clone());
+
+        let output = write_frame(&mut renderer, &buffer, "a\u{7}界b");
+
+        let cells = output.split("\x1b[49m").nth(1).unwrap();
+        assert_eq!(cells, "a?界\x1b[1;4Hb\x1b[0m");
+    }
+}
@@ -1,5 +1,4 @@
-use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::hash::Hash;
 use std::marker::PhantomData;
 use std::ops::Deref;
@@ -7,15 +6,27 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use super::ecs::{self, ComponentManager, EntityManager, SystemManager, UnsafeComponentCell};
-use super::{Component, Entity, System};
+use super::timer::{Timer, TimerHandle, TimerInterval, Timers};
+use super::{Component, Entity, Logger, ParallelSystem, SceneStats, System};
+
+mod transition;
+
+use transition::TransitionState;
+pub use transition::{Transition, TransitionKind};
 
 /// A Scene Handle, guaranteed to be unique per scene
+///
+/// Scene ids are reused once a scene is destroyed, the generation makes sure
+/// handles to a destroyed scene don't refer to the scene that reuses its id
 #[derive(Clone, Copy, Debug)]
-pub struct Scene(u32);
+pub struct Scene {
+    id: u32,
+    generation: u32,
+}
 
 impl PartialEq for Scene {
     fn eq(&self, other: &Self) -> bool {
-        self.0 == other.0
+        self.id == other.id && self.generation == other.generation
     }
 }
 
@@ -23,7 +34,8 @@ impl Eq for Scene {}
 
 impl Hash for Scene {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.0.hash(state)
+        self.id.hash(state);
+        self.generation.hash(state);
     }
 }
 
@@ -31,16 +43,21 @@ pub struct SceneState {
     entity_manager: EntityManager,
     component_manager: ComponentManager,
     system_manager: SystemManager,
-    entities_to_kill: RefCell<HashSet<Entity>>,
+    /// Entities destroyed during the frame, systems running in parallel can add to it
+    entities_to_kill: Mutex<HashSet<Entity>>,
+    timers: Timers,
+    logger: Arc<Logger>,
 }
 
 impl SceneState {
-    pub fn new() -> Self {
+    pub(crate) fn new(logger: Arc<Logger>) -> Self {
         Self {
+            logger,
             entity_manager: EntityManager::new(),
             component_manager: ComponentManager::new(),
             system_manager: SystemManager::new(),
-            entities_to_kill: RefCell::new(HashSet::new()),
+            entities_to_kill: Mutex::new(HashSet::new()),
+            timers: Timers::new(),
         }
     }
 
@@ -58,7 +75,7 @@ impl SceneState {
         let entity_exists = self.entity_manager.does_entity_exist(&entity);
 
         if entity_exists {
-            self.entities_to_kill.borrow_mut().insert(entity);
+            self.entities_to_kill.lock().unwrap().insert(entity);
             Ok(())
         } else {
             Err(ecs::ErrorKind::EntityDoesNotExist.into())
@@ -70,16 +87,57 @@ impl SceneState {
         self.entity_manager.get_living_entities()
     }
 
+    /// Counts the currently living entities in the scene
+    pub fn living_entity_count(&self) -> usize {
+        self.entity_manager.living_entity_count()
+    }
+
+    /// Counts how many entities in the scene own each registered component type, keyed by type name
+    pub fn component_counts(&self) -> HashMap<&'static str, usize> {
+        self.component_manager.component_counts()
+    }
+
     /// Destroys all amrked entities
     pub(crate) fn cull_entities(&self) -> Result<(), ecs::Error> {
-        for entity in self.entities_to_kill.take() {
+        let mut entities_to_kill = std::mem::take(&mut *self.entities_to_kill.lock().unwrap())
+            .into_iter()
+            .collect::<Vec<_>>();
+        // sorted so destroyed ids are reused in the same order every run
+        entities_to_kill.sort_unstable_by_key(Entity::id);
+
+        for entity in entities_to_kill {
             self.component_manager.remove_components(&entity)?;
+            self.timers.cancel_entity_timers(&entity);
 
             self.entity_manager.destroy_entity(entity);
         }
         Ok(())
     }
 
+    /// Checks if an entity exists and hasn't been marked for destruction
+    fn is_entity_alive(&self, entity: &Entity) -> bool {
+        self.entity_manager.does_entity_exist(entity)
+            && !self.entities_to_kill.lock().unwrap().contains(entity)
+    }
+
+    /// Moves an entity's components and timers onto an entity created in another scene
+    ///
+    /// The entity is then destroyed at the end of the frame, as with destroy_entity()
+    pub(crate) fn transfer_entity(
+        &self,
+        entity: &Entity,
+        target: &SceneState,
+        new_entity: &Entity,
+    ) -> Result<(), ecs::Error> {
+        self.component_manager
+            .move_components(entity, &target.component_manager, new_entity)?;
+        self.timers
+            .transfer_entity_timers(entity, &target.timers, new_entity);
+        self.entities_to_kill.lock().unwrap().insert(entity.clone());
+
+        Ok(())
+    }
+
     /// Registers a component for use in the scene and returns a handle to it
     ///
     /// Note: Components cannot be unregistered once registered
@@ -98,7 +156,9 @@ impl SceneState {
         if entity_exists {
             self.component_manager.add_component(entity, component)
         } else {
-            Err(ecs::ErrorKind::EntityDoesNotExist.into())
+            Err(ecs::Error::from(ecs::ErrorKind::EntityDoesNotExist)
+                .with_entity(entity)
+                .with_component(std::any::type_name::<C>()))
         }
     }
 
@@ -109,14 +169,17 @@ impl SceneState {
         if entity_exists {
             self.component_manager.remove_component::<C>(entity)
         } else {
-            Err(ecs::ErrorKind::EntityDoesNotExist.into())
+            Err(ecs::Error::from(ecs::ErrorKind::EntityDoesNotExist)
+                .with_entity(entity)
+                .with_component(std::any::type_name::<C>()))
         }
     }
 
     pub fn get_component<C: Send + 'static>(
         &self,
         entity: &Entity,
-    ) -> Result<UnsafeComponentCell<C>, ecs::Error> {
+    ) -> Result<UnsafeComponentCell<'_, C>, ecs::Error> {
+        ecs::check_component_access::<C>()?;
         self.component_manager.get_component::<C>(entity)
     }
 
@@ -129,46 +192,178 @@ impl SceneState {
         self.component_manager.has_components(entity, components)
     }
 
+    /// Starts a timer in the scene
+    ///
+    /// Timers count down while the scene runs frames, so they pause along with the scene
+    /// whenever it isn't current or transitioning out
+    pub fn start_timer(&self, timer: Timer) -> TimerHandle {
+        self.timers.start(None, timer)
+    }
+
+    /// Starts a timer attached to an entity, the timer is cancelled when the entity is destroyed
+    /// and moves with the entity when it is transferred to another scene
+    ///
+    /// # Errors
+    /// Returns an error if the entity doesn't exist in the scene
+    pub fn start_entity_timer(
+        &self,
+        entity: &Entity,
+        timer: Timer,
+    ) -> Result<TimerHandle, ecs::Error> {
+        if self.entity_manager.does_entity_exist(entity) {
+            Ok(self.timers.start(Some(entity.clone()), timer))
+        } else {
+            Err(ecs::Error::from(ecs::ErrorKind::EntityDoesNotExist).with_entity(entity))
+        }
+    }
+
+    /// Stops a timer before it fires again
+    ///
+    /// # Errors
+    /// Returns an error if the timer has finished or was cancelled
+    pub fn cancel_timer(&self, timer: TimerHandle) -> Result<(), ecs::Error> {
+        match self.timers.cancel(timer) {
+            true => Ok(()),
+            false => Err(ecs::ErrorKind::TimerDoesNotExist.into()),
+        }
+    }
+
+    /// Stops a timer from counting down until it is resumed
+    ///
+    /// # Errors
+    /// Returns an error if the timer has finished or was cancelled
+    pub fn pause_timer(&self, timer: TimerHandle) -> Result<(), ecs::Error> {
+        match self.timers.set_paused(timer, true) {
+            true => Ok(()),
+            false => Err(ecs::ErrorKind::TimerDoesNotExist.into()),
+        }
+    }
+
+    /// Lets a paused timer count down again
+    ///
+    /// # Errors
+    /// Returns an error if the timer has finished or was cancelled
+    pub fn resume_timer(&self, timer: TimerHandle) -> Result<(), ecs::Error> {
+        match self.timers.set_paused(timer, false) {
+            true => Ok(()),
+            false => Err(ecs::ErrorKind::TimerDoesNotExist.into()),
+        }
+    }
+
+    /// How long until a timer fires next, None if it has finished or was cancelled
+    pub fn timer_remaining(&self, timer: TimerHandle) -> Option<TimerInterval> {
+        self.timers.remaining(timer)
+    }
+
+    /// Checks if a timer attached to the entity, or started in the scene if entity is None,
+    /// fired the event this frame
+    ///
+    /// Timers fire before any system runs, so every system sees the events of the frame
+    pub fn has_timer_event(&self, entity: Option<&Entity>, event: &str) -> bool {
+        self.timers.has_event(entity, event)
+    }
+
+    /// The events fired this frame by the timers attached to the entity, or started in the
+    /// scene if entity is None, in the order they fired
+    pub fn timer_events(&self, entity: Option<&Entity>) -> Vec<String> {
+        self.timers.events(entity)
+    }
+
     /// Registers a system to be used in the scene
     ///
     /// Note: Systems cannot be unregistered once registered
     ///
-    /// # Errors
     /// Accessing any component of an entity other than the ones provided when registering the system
-    /// is considered undefined behaviour and should be avoided
-    pub fn register_system<S: System + 'static>(&self, signature: &[Component], system: S) {
+    /// is considered undefined behaviour and should be avoided, EngineConfig::check_component_access
+    /// turns getting such a component into an error
+    ///
+    /// # Errors
+    /// Returns an error if a component in the signature isn't registered in the scene
+    pub fn register_system<S: System + 'static>(
+        &self,
+        signature: &[Component],
+        system: S,
+    ) -> Result<(), ecs::Error> {
+        self.validate_signature::<S>(signature)?;
+
+        self.logger.debug(
+            "ecs",
+            format_args!("registering system {}", std::any::type_name::<S>()),
+        );
         self.system_manager.register_system::<S>(signature, system);
+
+        Ok(())
+    }
+
+    /// Registers a system whose entities are processed on every worker thread at once
+    ///
+    /// Note: Systems cannot be unregistered once registered
+    ///
+    /// Accessing any component of an entity other than the one being processed, or any
+    /// component outside of the signature, is considered undefined behaviour and should be avoided
+    ///
+    /// # Errors
+    /// Returns an error if a component in the signature isn't registered in the scene
+    pub fn register_parallel_system<S: ParallelSystem + 'static>(
+        &self,
+        signature: &[Component],
+        system: S,
+    ) -> Result<(), ecs::Error> {
+        self.validate_signature::<S>(signature)?;
+
+        self.logger.debug(
+            "ecs",
+            format_args!("registering parallel system {}", std::any::type_name::<S>()),
+        );
+        self.system_manager
+            .register_parallel_system::<S>(signature, system);
+
+        Ok(())
+    }
+
+    /// Checks that every component in a system's signature is registered in the scene
+    ///
+    /// Any mix of registered components can be matched, so this rejects every signature that can't
+    fn validate_signature<S: 'static>(&self, signature: &[Component]) -> Result<(), ecs::Error> {
+        let unregistered = signature
+            .iter()
+            .find(|component| !self.component_manager.is_component_registered(component));
+
+        match unregistered {
+            Some(component) => Err(ecs::Error::from(ecs::ErrorKind::ComponentNotRegistered)
+                .with_system(std::any::type_name::<S>())
+                // a type never registered in any scene is most likely not a component at all
+                .with_component(ecs::component_name(component).unwrap_or("unknown type"))),
+            None => Ok(()),
+        }
     }
 
     /// Executes the on_entry method of ever registered system in the scene
+    ///
+    /// Errors returned by systems are passed to the engine's error handler
     pub(crate) fn on_entry(&self, engine: Arc<crate::Engine>) {
         // TODO: Load Scene
-        self.system_manager.on_entry(engine);
+        self.system_manager.on_entry(engine)
     }
 
     /// Executes the on_exit method of every registered system in the scene
     pub(crate) fn on_exit(&self, engine: Arc<crate::Engine>) {
-        self.system_manager.on_exit(engine);
         // TODO: Destroy Scene
+        self.system_manager.on_exit(engine)
     }
 
     /// Executes the on_frame method of ever registered system in the scene
     ///
-    /// if is_physics_frame is true, it will also run the on_physics_frame method
-    /// before running the on_frame method
-    pub(crate) fn on_frame(
-        &self,
-        engine: Arc<crate::Engine>,
-        is_physics_frame: bool,
-        dt: Duration,
-    ) {
-        self.system_manager.on_frame(engine, is_physics_frame, dt);
-    }
-}
+    /// it will also run the on_physics_frame method physics_steps times
+    /// before running the on_frame method, the scene's timers are counted
+    /// down by dt and physics_steps before any system runs
+    pub(crate) fn on_frame(&self, engine: Arc<crate::Engine>, physics_steps: u32, dt: Duration) {
+        {
+            let _timers_span = engine.profiler().span("timers", "scene");
+            self.timers.advance(&engine, physics_steps, dt);
+        }
 
-impl Default for SceneState {
-    fn default() -> Self {
-        Self::new()
+        self.system_manager.on_frame(engine, physics_steps, dt)
     }
 }
 
@@ -187,85 +382,421 @@ impl<'a> Deref for UnsafeSceneStateCell<'a> {
 
 pub struct SceneManager {
     next_scene_id: Mutex<u32>,
-    scenes: Mutex<HashMap<Scene, SceneState>>,
+    /// Boxed so a live UnsafeSceneStateCell stays valid when creating a scene grows the map
+    scenes: Mutex<HashMap<Scene, Box<SceneState>>>,
+    /// The current generation of every scene id that has been handed out
+    generations: Mutex<Vec<u32>>,
+    dead_scene_ids: Mutex<VecDeque<u32>>,
+    /// Scenes destroyed while a scene was being updated, destroyed at the start of the next frame
+    scenes_to_destroy: Mutex<Vec<Scene>>,
 
     current_scene: Mutex<Option<Scene>>,
-    next_scene: Mutex<Option<Scene>>,
+    next_scene: Mutex<Option<(Scene, Transition)>>,
+    transition: Mutex<Option<TransitionState>>,
+
+    /// The scene whose systems are currently being run, if any
+    updating_scene: Mutex<Option<Scene>>,
+
+    logger: Arc<Logger>,
 }
 
 impl SceneManager {
-    pub fn new() -> Self {
+    pub(crate) fn new(logger: Arc<Logger>) -> Self {
         Self {
+            logger,
             next_scene_id: Mutex::new(0),
             scenes: Mutex::new(HashMap::new()),
+            generations: Mutex::new(Vec::new()),
+            dead_scene_ids: Mutex::new(VecDeque::new()),
+            scenes_to_destroy: Mutex::new(Vec::new()),
             current_scene: Mutex::new(None),
             next_scene: Mutex::new(None),
+            transition: Mutex::new(None),
+            updating_scene: Mutex::new(None),
         }
     }
 
     /// Creates a new scene and returns a handle to it
     pub fn create_scene(&self) -> Result<Scene, ecs::Error> {
-        if *self.next_scene_id.lock().unwrap() == u32::MAX {
-            Err(ecs::ErrorKind::SceneMaxReached.into())
+        let dead_scene_id = self.dead_scene_ids.lock().unwrap().pop_front();
+
+        let scene_handle = if let Some(id) = dead_scene_id {
+            Scene {
+                id,
+                generation: self.generations.lock().unwrap()[id as usize],
+            }
+        } else if *self.next_scene_id.lock().unwrap() == u32::MAX {
+            return Err(ecs::ErrorKind::SceneMaxReached.into());
         } else {
-            let scene_handle = Scene(*self.next_scene_id.lock().unwrap());
+            let id = *self.next_scene_id.lock().unwrap();
             *self.next_scene_id.lock().unwrap() += 1;
+            self.generations.lock().unwrap().push(0);
+
+            Scene { id, generation: 0 }
+        };
 
-            self.scenes
-                .lock()
-                .unwrap()
-                .insert(scene_handle, SceneState::default());
+        self.scenes.lock().unwrap().insert(
+            scene_handle,
+            Box::new(SceneState::new(Arc::clone(&self.logger))),
+        );
 
-            Ok(scene_handle)
+        self.logger
+            .debug("scene", format_args!("created scene {scene_handle:?}"));
+
+        Ok(scene_handle)
+    }
+
+    /// Destroys a scene, freeing its state and allowing its id to be reused
+    ///
+    /// If the scene is active its on_exit is run first. If this is called while
+    /// systems are running, the scene is destroyed at the start of the next frame
+    pub fn destroy_scene(
+        &self,
+        scene: &Scene,
+        engine: Arc<crate::Engine>,
+    ) -> Result<(), ecs::Error> {
+        if !self.does_scene_exist(scene) {
+            return Err(ecs::Error::from(ecs::ErrorKind::SceneDoesNotExist).with_scene(*scene));
+        }
+
+        if self.updating_scene.lock().unwrap().is_some() {
+            self.logger.debug(
+                "scene",
+                format_args!("deferring destruction of scene {scene:?} to the next frame"),
+            );
+            self.scenes_to_destroy.lock().unwrap().push(*scene);
+            return Ok(());
         }
+
+        if self.get_outgoing_scene_handle() == Some(*scene) {
+            self.finish_transition(Arc::clone(&engine));
+        }
+
+        let current_scene = *self.current_scene.lock().unwrap();
+        if current_scene == Some(*scene) {
+            // the incoming scene stops transitioning in, the outgoing scene keeps its on_exit
+            self.finish_transition(Arc::clone(&engine));
+            self.run_as(*scene, |state| state.on_exit(Arc::clone(&engine)));
+            *self.current_scene.lock().unwrap() = None;
+        }
+
+        engine.stats().remove_scene(scene);
+
+        self.free_scene(scene)
+    }
+
+    /// Moves entities and all of their components from one scene to another
+    ///
+    /// The moved entities get new handles in the target scene, the returned map
+    /// goes from the old handles to the new ones. Old handles are destroyed at the
+    /// end of the frame and have no components left once moved. Entities marked
+    /// for destruction can't be moved
+    ///
+    /// Timers attached to the entities move with them and keep their handles, which then
+    /// refer to timers in the target scene
+    ///
+    /// # Errors
+    ///
+    /// If any entity can't be moved none of them are
+    pub fn transfer_entities(
+        &self,
+        from: &Scene,
+        to: &Scene,
+        entities: Vec<Entity>,
+    ) -> Result<HashMap<Entity, Entity>, ecs::Error> {
+        let source = self.get_scene(from)?;
+        let target = self.get_scene(to)?;
+
+        if from == to {
+            return Ok(entities.into_iter().map(|e| (e.clone(), e)).collect());
+        }
+
+        // an entity listed twice is only moved once
+        let mut listed = HashSet::with_capacity(entities.len());
+        let entities = entities
+            .into_iter()
+            .filter(|e| listed.insert(e.clone()))
+            .collect::<Vec<_>>();
+
+        if let Some(entity) = entities.iter().find(|e| !source.is_entity_alive(e)) {
+            return Err(ecs::Error::from(ecs::ErrorKind::EntityDoesNotExist)
+                .with_scene(*from)
+                .with_entity(entity));
+        }
+
+        // every new entity is created before anything moves, creating them is the only step that can fail
+        let mut new_entities = Vec::with_capacity(entities.len());
+        for _ in &entities {
+            match target.create_entity() {
+                Ok(new_entity) => new_entities.push(new_entity),
+                Err(err) => {
+                    for new_entity in new_entities {
+                        target.entity_manager.destroy_entity(new_entity);
+                    }
+                    return Err(err.with_scene(*to));
+                }
+            }
+        }
+
+        let mut entity_map = HashMap::with_capacity(entities.len());
+
+        for (entity, new_entity) in entities.into_iter().zip(new_entities) {
+            source.transfer_entity(&entity, &target, &new_entity)?;
+            entity_map.insert(entity, new_entity);
+        }
+
+        self.logger.debug(
+            "scene",
+            format_args!(
+                "moved {} entities from scene {from:?} to scene {to:?}",
+                entity_map.len()
+            ),
+        );
+
+        Ok(entity_map)
+    }
+
+    /// Removes a scene's state without running any of its systems
+    ///
+    /// Only used directly when no scene can be active, before the engine starts running
+    pub(crate) fn free_scene(&self, scene: &Scene) -> Result<(), ecs::Error> {
+        let state = self.scenes.lock().unwrap().remove(scene).ok_or_else(|| {
+            ecs::Error::from(ecs::ErrorKind::SceneDoesNotExist).with_scene(*scene)
+        })?;
+        // dropped outside of the lock in case dropping the state touches the scene manager
+        drop(state);
+
+        self.logger
+            .debug("scene", format_args!("destroyed scene {scene:?}"));
+
+        let mut next_scene = self.next_scene.lock().unwrap();
+        if matches!(*next_scene, Some((next, _)) if next == *scene) {
+            *next_scene = None;
+        }
+
+        // wraps rather than panicking, a handle would have to outlive 2^32 reuses of its id to be mistaken
+        let generation = &mut self.generations.lock().unwrap()[scene.id as usize];
+        *generation = generation.wrapping_add(1);
+        self.dead_scene_ids.lock().unwrap().push_back(scene.id);
+
+        Ok(())
     }
 
     /// Retrieves a mutable reference to the requested scene
-    pub fn get_scene(&self, scene: &Scene) -> Result<UnsafeSceneStateCell, ecs::Error> {
+    pub fn get_scene(&self, scene: &Scene) -> Result<UnsafeSceneStateCell<'_>, ecs::Error> {
         if self.does_scene_exist(scene) {
             let scenes = self.scenes.lock().unwrap();
 
-            let scene = scenes
-                .get(scene)
-                .ok_or(ecs::Error::from(ecs::ErrorKind::SceneDoesNotExist))?;
+            let scene = scenes.get(scene).ok_or_else(|| {
+                ecs::Error::from(ecs::ErrorKind::SceneDoesNotExist).with_scene(*scene)
+            })?;
 
             Ok(UnsafeSceneStateCell {
-                data: std::ptr::from_ref(scene),
+                data: std::ptr::from_ref(scene.as_ref()),
                 _owns: PhantomData,
             })
         } else {
-            Err(ecs::ErrorKind::SceneDoesNotExist.into())
+            Err(ecs::Error::from(ecs::ErrorKind::SceneDoesNotExist).with_scene(*scene))
         }
     }
 
     /// Retrieves a mutable reference to the current scene
-    pub fn get_current_scene(&self) -> Result<UnsafeSceneStateCell, ecs::Error> {
-        if let Some(scene) = *self.current_scene.lock().unwrap() {
-            self.get_scene(&scene)
-        } else {
-            Err(ecs::ErrorKind::NoCurrentScene.into())
-        }
+    ///
+    /// While a scene's systems are running this is the scene that owns them,
+    /// so the systems of an outgoing scene still see their own scene during a transition
+    pub fn get_current_scene(&self) -> Result<UnsafeSceneStateCell<'_>, ecs::Error> {
+        let scene = self.get_current_scene_handle()?;
+        self.get_scene(&scene)
+    }
+
+    /// Retrieves a handle to the current scene
+    ///
+    /// Follows the same rules as SceneManager::get_current_scene()
+    pub fn get_current_scene_handle(&self) -> Result<Scene, ecs::Error> {
+        let updating_scene = *self.updating_scene.lock().unwrap();
+
+        updating_scene
+            .or(*self.current_scene.lock().unwrap())
+            .ok_or(ecs::ErrorKind::NoCurrentScene.into())
+    }
+
+    /// Retrieves a handle to the scene being transitioned away from, if a transition is in progress
+    pub fn get_outgoing_scene_handle(&self) -> Option<Scene> {
+        self.transition.lock().unwrap().as_ref().map(|t| t.from)
+    }
+
+    /// Retrieves the transition that is currently in progress
+    pub fn get_transition(&self) -> Option<Transition> {
+        self.transition
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|t| t.transition)
+    }
+
+    /// Retrieves how far along the current transition is, from 0.0 to 1.0
+    ///
+    /// Returns None if there is no transition in progress
+    pub fn transition_progress(&self) -> Option<f32> {
+        self.transition
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(TransitionState::progress)
     }
 
     /// Sets the current scene
+    ///
+    /// The scene is swapped at the start of the next frame
     pub fn set_current_scene(&self, scene: &Scene) -> Result<(), ecs::Error> {
+        self.set_current_scene_with(scene, Transition::cut())
+    }
+
+    /// Sets the current scene, transitioning to it over time
+    ///
+    /// The incoming scene's on_entry runs when the transition starts, and the outgoing
+    /// scene's on_exit runs when it ends, both scenes run every frame in between
+    pub fn set_current_scene_with(
+        &self,
+        scene: &Scene,
+        transition: Transition,
+    ) -> Result<(), ecs::Error> {
         if self.does_scene_exist(scene) {
-            *self.next_scene.lock().unwrap() = Some(*scene);
+            *self.next_scene.lock().unwrap() = Some((*scene, transition));
             Ok(())
         } else {
-            Err(ecs::ErrorKind::SceneDoesNotExist.into())
+            Err(ecs::Error::from(ecs::ErrorKind::SceneDoesNotExist).with_scene(*scene))
         }
     }
 
-    // Swaps scenes if next scene is set
-    pub fn swap_scenes(&self, engine: Arc<crate::Engine>) {
-        if let Some(scene) = self.next_scene.lock().unwrap().take() {
-            if let Ok(scene) = self.get_current_scene() {
-                scene.on_exit(Arc::clone(&engine));
+    // Advances the current transition and swaps scenes if next scene is set
+    pub fn swap_scenes(&self, engine: Arc<crate::Engine>, dt: Duration) -> Result<(), ecs::Error> {
+        let scenes_to_destroy = std::mem::take(&mut *self.scenes_to_destroy.lock().unwrap());
+
+        for scene in scenes_to_destroy {
+            // the scene may have been destroyed twice in the same frame
+            if self.does_scene_exist(&scene) {
+                self.destroy_scene(&scene, Arc::clone(&engine))?;
             }
+        }
+
+        let is_transition_finished = self
+            .transition
+            .lock()
+            .unwrap()
+            .as_mut()
+            .map(|t| {
+                t.elapsed += dt;
+                t.is_finished()
+            })
+            .unwrap_or(false);
+
+        if is_transition_finished {
+            self.finish_transition(Arc::clone(&engine));
+        }
+
+        let next_scene = self.next_scene.lock().unwrap().take();
+
+        if let Some((scene, transition)) = next_scene {
+            // only one transition can run at a time
+            self.finish_transition(Arc::clone(&engine));
+
+            let current_scene = *self.current_scene.lock().unwrap();
+
+            match current_scene {
+                Some(current_scene) => self.logger.info(
+                    "scene",
+                    format_args!(
+                        "swapping from scene {current_scene:?} to scene {scene:?} ({:?} over {:?})",
+                        transition.kind, transition.duration
+                    ),
+                ),
+                None => self.logger.info(
+                    "scene",
+                    format_args!("entering scene {scene:?} with no current scene"),
+                ),
+            }
+
+            match current_scene {
+                Some(current_scene) if !transition.is_instant() && current_scene != scene => {
+                    *self.transition.lock().unwrap() =
+                        Some(TransitionState::new(current_scene, transition));
+                }
+                Some(current_scene) => {
+                    self.run_as(current_scene, |state| state.on_exit(Arc::clone(&engine)));
+                }
+                None => {}
+            }
+
             *self.current_scene.lock().unwrap() = Some(scene);
-            self.get_current_scene().unwrap().on_entry(engine)
+            self.run_as(scene, |state| state.on_entry(engine));
         }
+
+        Ok(())
+    }
+
+    /// Runs a frame of the outgoing scene and then the current scene, if they exist
+    pub(crate) fn on_frame(
+        &self,
+        engine: Arc<crate::Engine>,
+        physics_steps: u32,
+        dt: Duration,
+    ) -> Result<(), ecs::Error> {
+        for scene in self.active_scenes() {
+            self.run_as(scene, |state| {
+                state.on_frame(Arc::clone(&engine), physics_steps, dt);
+
+                {
+                    let _cull_span = engine.profiler().span("cull_entities", "scene");
+                    state.cull_entities()?;
+                }
+
+                engine.stats().record_scene(
+                    scene,
+                    SceneStats {
+                        entity_count: state.living_entity_count(),
+                        component_counts: state.component_counts(),
+                    },
+                );
+                Ok::<_, ecs::Error>(())
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// The outgoing scene of a transition, if any, followed by the current scene
+    pub(crate) fn active_scenes(&self) -> Vec<Scene> {
+        let mut active_scenes = Vec::with_capacity(2);
+        active_scenes.extend(self.get_outgoing_scene_handle());
+        active_scenes.extend(*self.current_scene.lock().unwrap());
+        active_scenes
+    }
+
+    /// Ends the current transition early, running the outgoing scene's on_exit
+    fn finish_transition(&self, engine: Arc<crate::Engine>) {
+        let transition = self.transition.lock().unwrap().take();
+
+        if let Some(transition) = transition {
+            self.logger.debug(
+                "scene",
+                format_args!("finished transition from scene {:?}", transition.from),
+            );
+            self.run_as(transition.from, |state| state.on_exit(engine));
+        }
+    }
+
+    /// Runs the given function on a scene while marking it as the scene being updated
+    fn run_as<R>(&self, scene: Scene, f: impl FnOnce(&SceneState) -> R) -> R {
+        let state = self
+            .get_scene(&scene)
+            .expect("Failed to get scene that is being updated!");
+
+        let previous = self.updating_scene.lock().unwrap().replace(scene);
+        let result = f(&state);
+        *self.updating_scene.lock().unwrap() = previous;
+
+        result
     }
 
     /// Checks if a scene exists
@@ -274,8 +805,346 @@ impl SceneManager {
     }
 }
 
-impl Default for SceneManager {
-    fn default() -> Self {
-        Self::new()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ecs::System, timer::Timer, Engine};
+
+    #[derive(Debug, PartialEq)]
+    struct Health(u32);
+
+    #[test]
+    fn transfers_move_components_and_timers() {
+        let mut engine = Engine::new();
+        let (from, to) = (
+            engine.create_scene().unwrap(),
+            engine.create_scene().unwrap(),
+        );
+        let scenes = engine.scenes();
+        let (source, target) = (
+            scenes.get_scene(&from).unwrap(),
+            scenes.get_scene(&to).unwrap(),
+        );
+        source.register_component::<Health>();
+
+        let entity = source.create_entity().unwrap();
+        source.add_component(&entity, Health(3)).unwrap();
+        let timer = source
+            .start_entity_timer(&entity, Timer::after(Duration::from_secs(1)))
+            .unwrap();
+        let other = source.create_entity().unwrap();
+
+        // listing an entity twice only moves it once
+        let moved = scenes
+            .transfer_entities(&from, &to, vec![entity.clone(), entity.clone()])
+            .unwrap();
+        assert_eq!(moved.len(), 1);
+        let new_entity = &moved[&entity];
+
+        assert_eq!(
+            *target.get_component::<Health>(new_entity).unwrap(),
+            Health(3)
+        );
+        assert!(target.timer_remaining(timer).is_some());
+        assert!(source.timer_remaining(timer).is_none());
+        assert!(source.get_component::<Health>(&entity).is_err());
+
+        // the old handle can't be moved again and is destroyed with the frame's other entities
+        assert!(scenes
+            .transfer_entities(&from, &to, vec![entity.clone()])
+            .is_err());
+        assert_eq!(source.living_entity_count(), 2);
+        source.cull_entities().unwrap();
+        assert_eq!(source.get_living_entities(), vec![other]);
+        assert_eq!(target.living_entity_count(), 1);
+    }
+
+    #[test]
+    fn failed_transfers_move_nothing() {
+        let mut engine = Engine::new();
+        let (from, to) = (
+            engine.create_scene().unwrap(),
+            engine.create_scene().unwrap(),
+        );
+        let scenes = engine.scenes();
+        let (source, target) = (
+            scenes.get_scene(&from).unwrap(),
+            scenes.get_scene(&to).unwrap(),
+        );
+        source.register_component::<Health>();
+
+        let entity = source.create_entity().unwrap();
+        source.add_component(&entity, Health(3)).unwrap();
+        let destroyed = source.create_entity().unwrap();
+        source.destroy_entity(destroyed.clone()).unwrap();
+
+        let err = scenes
+            .transfer_entities(&from, &to, vec![entity.clone(), destroyed])
+            .unwrap_err();
+        assert!(matches!(err.kind(), ecs::ErrorKind::EntityDoesNotExist));
+
+        assert_eq!(*source.get_component::<Health>(&entity).unwrap(), Health(3));
+        assert_eq!(target.living_entity_count(), 0);
+        assert!(scenes.transfer_entities(&from, &to, vec![entity]).is_ok());
+    }
+
+    struct Marker;
+
+    /// Logs every entry, exit and frame along with the scene the systems see as current
+    struct Recorder {
+        label: &'static str,
+        scene: Scene,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Recorder {
+        fn record(&self, engine: &Engine, event: &str) {
+            let current = engine.scenes().get_current_scene_handle().unwrap();
+            let event = match current == self.scene {
+                true => format!("{} {event}", self.label),
+                false => format!("{} {event} in another scene", self.label),
+            };
+            self.log.lock().unwrap().push(event);
+        }
+    }
+
+    impl System for Recorder {
+        fn on_entry(&mut self, engine: Arc<Engine>, _: Entity) -> Result<(), ecs::Error> {
+            self.record(&engine, "entry");
+            Ok(())
+        }
+
+        fn on_exit(&mut self, engine: Arc<Engine>, _: Entity) -> Result<(), ecs::Error> {
+            self.record(&engine, "exit");
+            Ok(())
+        }
+
+        fn on_frame(
+            &mut self,
+            engine: Arc<Engine>,
+            _: Entity,
+            _: Duration,
+        ) -> Result<(), ecs::Error> {
+            self.record(&engine, "frame");
+            Ok(())
+        }
+    }
+
+    /// An engine with a scene per label, each with one entity its Recorder runs on
+    fn recorded_scenes(
+        labels: &[&'static str],
+    ) -> (Arc<Engine>, Vec<Scene>, Arc<Mutex<Vec<String>>>) {
+        let mut engine = Engine::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let scenes = labels
+            .iter()
+            .map(|&label| {
+                let scene = engine.create_scene().unwrap();
+                let state = engine.scenes().get_scene(&scene).unwrap();
+                let marker = state.register_component::<Marker>();
+                let entity = state.create_entity().unwrap();
+                state.add_component(&entity, Marker).unwrap();
+
+                let log = Arc::clone(&log);
+                state
+                    .register_system(&[marker], Recorder { label, scene, log })
+                    .unwrap();
+                scene
+            })
+            .collect();
+
+        (Arc::new(engine), scenes, log)
+    }
+
+    fn take(log: &Mutex<Vec<String>>) -> Vec<String> {
+        std::mem::take(&mut *log.lock().unwrap())
+    }
+
+    #[test]
+    fn cuts_exit_the_old_scene_before_entering_the_new_one() {
+        let (engine, scenes, log) = recorded_scenes(&["a", "b"]);
+        let manager = engine.scenes();
+
+        manager.set_current_scene(&scenes[0]).unwrap();
+        manager
+            .swap_scenes(Arc::clone(&engine), Duration::ZERO)
+            .unwrap();
+        manager.set_current_scene(&scenes[1]).unwrap();
+        manager
+            .swap_scenes(Arc::clone(&engine), Duration::ZERO)
+            .unwrap();
+
+        assert_eq!(take(&log), ["a entry", "a exit", "b entry"]);
+        assert_eq!(manager.get_outgoing_scene_handle(), None);
+        assert_eq!(manager.get_current_scene_handle().unwrap(), scenes[1]);
+    }
+
+    #[test]
+    fn cross_fades_run_both_scenes_as_themselves() {
+        let (engine, scenes, log) = recorded_scenes(&["a", "b"]);
+        let manager = engine.scenes();
+        let second = Duration::from_secs(1);
+
+        manager.set_current_scene(&scenes[0]).unwrap();
+        manager
+            .swap_scenes(Arc::clone(&engine), Duration::ZERO)
+            .unwrap();
+        manager
+            .set_current_scene_with(&scenes[1], Transition::cross_fade(second))
+            .unwrap();
+        manager
+            .swap_scenes(Arc::clone(&engine), Duration::ZERO)
+            .unwrap();
+        assert_eq!(manager.get_outgoing_scene_handle(), Some(scenes[0]));
+        assert_eq!(manager.transition_progress(), Some(0.0));
+
+        manager
+            .on_frame(Arc::clone(&engine), 0, second / 2)
+            .unwrap();
+        manager
+            .swap_scenes(Arc::clone(&engine), second / 2)
+            .unwrap();
+        assert_eq!(manager.transition_progress(), Some(0.5));
+        manager
+            .swap_scenes(Arc::clone(&engine), second / 2)
+            .unwrap();
+        assert_eq!(manager.get_transition(), None);
+
+        assert_eq!(
+            take(&log),
+            ["a entry", "b entry", "a frame", "b frame", "a exit"]
+        );
+    }
+
+    #[test]
+    fn new_transitions_finish_the_pending_one_first() {
+        let (engine, scenes, log) = recorded_scenes(&["a", "b", "c"]);
+        let manager = engine.scenes();
+        let fade = Transition::cross_fade(Duration::from_secs(1));
+
+        manager.set_current_scene(&scenes[0]).unwrap();
+        manager
+            .swap_scenes(Arc::clone(&engine), Duration::ZERO)
+            .unwrap();
+        manager.set_current_scene_with(&scenes[1], fade).unwrap();
+        manager
+            .swap_scenes(Arc::clone(&engine), Duration::ZERO)
+            .unwrap();
+
+        manager.set_current_scene_with(&scenes[2], fade).unwrap();
+        manager
+            .swap_scenes(Arc::clone(&engine), Duration::ZERO)
+            .unwrap();
+        assert_eq!(take(&log), ["a entry", "b entry", "a exit", "c entry"]);
+        assert_eq!(manager.get_outgoing_scene_handle(), Some(scenes[1]));
+
+        // only the last scene requested before a swap is entered
+        manager.set_current_scene(&scenes[0]).unwrap();
+        manager.set_current_scene(&scenes[1]).unwrap();
+        manager
+            .swap_scenes(Arc::clone(&engine), Duration::ZERO)
+            .unwrap();
+        assert_eq!(take(&log), ["b exit", "c exit", "b entry"]);
+        assert_eq!(manager.get_current_scene_handle().unwrap(), scenes[1]);
+    }
+
+    #[test]
+    fn stale_scene_handles_are_rejected_after_their_id_is_reused() {
+        let engine = Arc::new(Engine::new());
+        let manager = engine.scenes();
+
+        let scene = manager.create_scene().unwrap();
+        manager.destroy_scene(&scene, Arc::clone(&engine)).unwrap();
+        assert!(manager.get_scene(&scene).is_err());
+
+        let reused = manager.create_scene().unwrap();
+        assert_eq!(reused.id, scene.id);
+        assert!(manager.get_scene(&reused).is_ok());
+        assert!(manager.get_scene(&scene).is_err());
+        assert!(manager.set_current_scene(&scene).is_err());
+        assert!(manager.destroy_scene(&scene, Arc::clone(&engine)).is_err());
+        assert!(manager.get_scene(&reused).is_ok());
+    }
+
+    #[test]
+    fn scene_generations_wrap_around() {
+        let engine = Arc::new(Engine::new());
+        let manager = engine.scenes();
+
+        let scene = manager.create_scene().unwrap();
+        manager.generations.lock().unwrap()[scene.id as usize] = u32::MAX;
+        manager.destroy_scene(&scene, Arc::clone(&engine)).unwrap();
+
+        assert_eq!(manager.create_scene().unwrap().generation, 0);
+    }
+
+    #[test]
+    fn scene_states_stay_put_while_scenes_are_created() {
+        let engine = Engine::new();
+        let manager = engine.scenes();
+
+        let scene = manager.create_scene().unwrap();
+        let state = manager.get_scene(&scene).unwrap();
+        for _ in 0..64 {
+            manager.create_scene().unwrap();
+        }
+
+        let moved = manager.get_scene(&scene).unwrap();
+        assert!(std::ptr::eq(&*state, &*moved));
+    }
+
+    /// Destroys a scene from inside a system
+    struct DestroySystem {
+        target: Scene,
+    }
+
+    impl System for DestroySystem {
+        fn on_frame(
+            &mut self,
+            engine: Arc<Engine>,
+            _: Entity,
+            _: Duration,
+        ) -> Result<(), ecs::Error> {
+            engine
+                .scenes()
+                .destroy_scene(&self.target, Arc::clone(&engine))
+        }
+    }
+
+    #[test]
+    fn scenes_destroyed_while_updating_are_destroyed_next_frame() {
+        let (engine, scenes, log) = recorded_scenes(&["a", "b"]);
+        let manager = engine.scenes();
+
+        let state = manager.get_scene(&scenes[0]).unwrap();
+        let marker = state.register_component::<Marker>();
+        state
+            .register_system(&[marker], DestroySystem { target: scenes[0] })
+            .unwrap();
+
+        manager.set_current_scene(&scenes[0]).unwrap();
+        manager
+            .swap_scenes(Arc::clone(&engine), Duration::ZERO)
+            .unwrap();
+        manager
+            .on_frame(Arc::clone(&engine), 0, Duration::ZERO)
+            .unwrap();
+        // the scene isn't freed while its own systems are running
+        assert!(manager.get_scene(&scenes[0]).is_ok());
+        assert_eq!(take(&log), ["a entry", "a frame"]);
+
+        manager
+            .swap_scenes(Arc::clone(&engine), Duration::ZERO)
+            .unwrap();
+        assert!(manager.get_scene(&scenes[0]).is_err());
+        assert!(manager.get_current_scene_handle().is_err());
+        assert_eq!(take(&log), ["a exit"]);
+
+        manager
+            .destroy_scene(&scenes[1], Arc::clone(&engine))
+            .unwrap();
+        assert!(manager.get_scene(&scenes[1]).is_err());
+        assert!(take(&log).is_empty());
     }
 }
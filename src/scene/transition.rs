@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use super::Scene;
+
+/// How the outgoing and incoming scenes should be blended during a transition
+///
+/// The engine does not draw anything itself, the kind is only recorded so
+/// systems can decide how to use the transition progress
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionKind {
+    /// Swaps scenes immediately, ignoring the transition duration
+    Cut,
+    /// Both scenes run for the duration of the transition
+    CrossFade,
+}
+
+/// Describes how the engine should switch from the current scene to the next one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Transition {
+    pub duration: Duration,
+    pub kind: TransitionKind,
+}
+
+impl Transition {
+    /// A transition that swaps scenes within a single frame
+    pub fn cut() -> Self {
+        Self {
+            duration: Duration::ZERO,
+            kind: TransitionKind::Cut,
+        }
+    }
+
+    /// A transition that runs both scenes for the given duration
+    pub fn cross_fade(duration: Duration) -> Self {
+        Self {
+            duration,
+            kind: TransitionKind::CrossFade,
+        }
+    }
+
+    /// Checks if the transition finishes in the same frame it starts
+    pub(crate) fn is_instant(&self) -> bool {
+        self.kind == TransitionKind::Cut || self.duration.is_zero()
+    }
+}
+
+impl Default for Transition {
+    fn default() -> Self {
+        Self::cut()
+    }
+}
+
+/// A transition that is currently in progress
+pub(crate) struct TransitionState {
+    pub from: Scene,
+    pub transition: Transition,
+    pub elapsed: Duration,
+}
+
+impl TransitionState {
+    pub fn new(from: Scene, transition: Transition) -> Self {
+        Self {
+            from,
+            transition,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// How far along the transition is, from 0.0 to 1.0
+    pub fn progress(&self) -> f32 {
+        if self.transition.duration.is_zero() {
+            1.0
+        } else {
+            (self.elapsed.as_secs_f32() / self.transition.duration.as_secs_f32()).min(1.0)
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.transition.duration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_runs_from_zero_to_one() {
+        let scene = Scene {
+            id: 0,
+            generation: 0,
+        };
+        let mut state = TransitionState::new(scene, Transition::cross_fade(Duration::from_secs(2)));
+        assert_eq!((state.progress(), state.is_finished()), (0.0, false));
+
+        state.elapsed = Duration::from_secs(1);
+        assert_eq!((state.progress(), state.is_finished()), (0.5, false));
+
+        state.elapsed = Duration::from_secs(3);
+        assert_eq!((state.progress(), state.is_finished()), (1.0, true));
+
+        assert!(Transition::cut().is_instant());
+        assert!(Transition::cross_fade(Duration::ZERO).is_instant());
+        assert_eq!(
+            TransitionState::new(scene, Transition::cut()).progress(),
+            1.0
+        );
+    }
+}
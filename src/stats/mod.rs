@@ -0,0 +1,242 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::Duration,
+};
+
+use super::Scene;
+
+/// Entity and component counts of a scene
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SceneStats {
+    pub entity_count: usize,
+    /// How many entities own each component type, keyed by type name
+    pub component_counts: HashMap<&'static str, usize>,
+}
+
+/// A single frame's measurements
+struct FrameSample {
+    frame_time: Duration,
+    physics_steps: u32,
+    /// Time the pool's workers spent running jobs during the frame
+    pool_busy: Duration,
+}
+
+struct StatsState {
+    samples: VecDeque<FrameSample>,
+    // running totals of the samples, so queries don't have to sum every frame in the window
+    total_frame_time: Duration,
+    total_physics_steps: u64,
+    total_pool_busy: Duration,
+    scenes: HashMap<Scene, SceneStats>,
+}
+
+/// Engine wide statistics, updated once per frame
+///
+/// Timing values are averaged over the frames in the last window of time,
+/// scene counts are taken from the most recent frame a scene was active in
+pub struct EngineStats {
+    window: Duration,
+    thread_count: usize,
+    state: Mutex<StatsState>,
+}
+
+impl EngineStats {
+    pub fn new(window: Duration, thread_count: usize) -> Self {
+        assert!(!window.is_zero());
+        Self {
+            window,
+            thread_count,
+            state: Mutex::new(StatsState {
+                samples: VecDeque::new(),
+                total_frame_time: Duration::ZERO,
+                total_physics_steps: 0,
+                total_pool_busy: Duration::ZERO,
+                scenes: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Records a frame's measurements
+    ///
+    /// pool_busy is the time the thread pool's workers spent running jobs during the frame
+    pub(crate) fn record_frame(
+        &self,
+        frame_time: Duration,
+        physics_steps: u32,
+        pool_busy: Duration,
+    ) {
+        let mut state = self.state.lock().unwrap();
+
+        state.total_frame_time += frame_time;
+        state.total_physics_steps += physics_steps as u64;
+        state.total_pool_busy += pool_busy;
+        state.samples.push_back(FrameSample {
+            frame_time,
+            physics_steps,
+            pool_busy,
+        });
+
+        // always keep the newest frame, even if it is longer than the window
+        while state.samples.len() > 1 && state.total_frame_time > self.window {
+            let oldest = state.samples.pop_front().unwrap();
+            state.total_frame_time -= oldest.frame_time;
+            state.total_physics_steps -= oldest.physics_steps as u64;
+            state.total_pool_busy -= oldest.pool_busy;
+        }
+    }
+
+    /// Records the counts of a scene that was active this frame
+    pub(crate) fn record_scene(&self, scene: Scene, stats: SceneStats) {
+        self.state.lock().unwrap().scenes.insert(scene, stats);
+    }
+
+    /// Forgets a scene that no longer exists
+    pub(crate) fn remove_scene(&self, scene: &Scene) {
+        self.state.lock().unwrap().scenes.remove(scene);
+    }
+
+    /// The length of time values are averaged over
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// The average time between frames
+    pub fn frame_time(&self) -> Duration {
+        let state = self.state.lock().unwrap();
+
+        if state.samples.is_empty() {
+            Duration::ZERO
+        } else {
+            state.total_frame_time / state.samples.len() as u32
+        }
+    }
+
+    /// The shortest frame time in the window
+    pub fn min_frame_time(&self) -> Duration {
+        let state = self.state.lock().unwrap();
+        let frame_times = state.samples.iter().map(|sample| sample.frame_time);
+
+        frame_times.min().unwrap_or_default()
+    }
+
+    /// The longest frame time in the window
+    pub fn max_frame_time(&self) -> Duration {
+        let state = self.state.lock().unwrap();
+        let frame_times = state.samples.iter().map(|sample| sample.frame_time);
+
+        frame_times.max().unwrap_or_default()
+    }
+
+    /// The average number of frames per second
+    pub fn frames_per_second(&self) -> f32 {
+        let frame_time = self.frame_time();
+
+        if frame_time.is_zero() {
+            0.0
+        } else {
+            1.0 / frame_time.as_secs_f32()
+        }
+    }
+
+    /// The average number of physics frames per second
+    pub fn physics_rate(&self) -> f32 {
+        let state = self.state.lock().unwrap();
+
+        if state.total_frame_time.is_zero() {
+            0.0
+        } else {
+            state.total_physics_steps as f32 / state.total_frame_time.as_secs_f32()
+        }
+    }
+
+    /// The average fraction of worker time spent running jobs, from 0.0 to 1.0
+    pub fn thread_pool_utilization(&self) -> f32 {
+        let state = self.state.lock().unwrap();
+        let pool_time = state.total_frame_time.as_secs_f32() * self.thread_count as f32;
+
+        if pool_time > 0.0 {
+            (state.total_pool_busy.as_secs_f32() / pool_time).min(1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// The entity and component counts of a scene, if it has been active
+    pub fn scene_stats(&self, scene: &Scene) -> Option<SceneStats> {
+        self.state.lock().unwrap().scenes.get(scene).cloned()
+    }
+
+    /// The number of living entities in a scene, if it has been active
+    pub fn entity_count(&self, scene: &Scene) -> Option<usize> {
+        self.state
+            .lock()
+            .unwrap()
+            .scenes
+            .get(scene)
+            .map(|stats| stats.entity_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn averages_frames_in_the_window() {
+        let stats = EngineStats::new(MS * 100, 2);
+        assert_eq!(stats.frame_time(), Duration::ZERO);
+        assert_eq!(stats.frames_per_second(), 0.0);
+
+        stats.record_frame(MS * 10, 1, MS * 10);
+        stats.record_frame(MS * 30, 3, MS * 20);
+        assert_eq!(stats.frame_time(), MS * 20);
+        assert!((stats.frames_per_second() - 50.0).abs() < 1e-3);
+        assert!((stats.physics_rate() - 100.0).abs() < 1e-3);
+        assert!((stats.thread_pool_utilization() - 0.375).abs() < 1e-6);
+        assert_eq!(
+            (stats.min_frame_time(), stats.max_frame_time()),
+            (MS * 10, MS * 30)
+        );
+    }
+
+    #[test]
+    fn drops_frames_that_left_the_window() {
+        let stats = EngineStats::new(MS * 100, 1);
+
+        stats.record_frame(MS * 5, 0, Duration::ZERO);
+        for _ in 0..4 {
+            stats.record_frame(MS * 25, 0, Duration::ZERO);
+        }
+        assert_eq!(stats.frame_time(), MS * 25);
+        assert_eq!(stats.min_frame_time(), MS * 25);
+
+        // a frame longer than the window replaces every other frame
+        stats.record_frame(MS * 500, 0, Duration::ZERO);
+        assert_eq!(stats.frame_time(), MS * 500);
+        assert_eq!(
+            (stats.min_frame_time(), stats.max_frame_time()),
+            (MS * 500, MS * 500)
+        );
+    }
+
+    #[test]
+    fn keeps_the_counts_of_active_scenes() {
+        let stats = EngineStats::new(MS, 1);
+        let scene = crate::Engine::new().create_scene().unwrap();
+        assert_eq!(stats.entity_count(&scene), None);
+
+        let scene_stats = SceneStats {
+            entity_count: 3,
+            component_counts: HashMap::from([("Transform", 2)]),
+        };
+        stats.record_scene(scene, scene_stats.clone());
+        assert_eq!(stats.entity_count(&scene), Some(3));
+        assert_eq!(stats.scene_stats(&scene), Some(scene_stats));
+
+        stats.remove_scene(&scene);
+        assert_eq!(stats.scene_stats(&scene), None);
+    }
+}
@@ -0,0 +1,295 @@
+use std::{
+    any::Any,
+    error, fmt,
+    sync::{Arc, Condvar, Mutex},
+};
+
+/// A job that did not finish
+pub struct JobError {
+    /// The panic payload, or None if the job was cancelled
+    payload: Option<Box<dyn Any + Send>>,
+}
+
+impl JobError {
+    pub(crate) fn panicked(payload: Box<dyn Any + Send>) -> Self {
+        Self {
+            payload: Some(payload),
+        }
+    }
+
+    pub(super) fn cancelled() -> Self {
+        Self { payload: None }
+    }
+
+    /// Checks if the job was dropped by the pool before it could run
+    pub fn is_cancelled(&self) -> bool {
+        self.payload.is_none()
+    }
+
+    /// Describes why the job did not finish, using the panic message if it panicked with a string
+    pub fn message(&self) -> &str {
+        match &self.payload {
+            None => "job was cancelled",
+            Some(payload) => {
+                if let Some(message) = payload.downcast_ref::<&'static str>() {
+                    message
+                } else if let Some(message) = payload.downcast_ref::<String>() {
+                    message
+                } else {
+                    "unknown panic payload"
+                }
+            }
+        }
+    }
+
+    /// Continues the panic on the current thread, or starts one if the job was cancelled
+    pub fn resume(self) -> ! {
+        match self.payload {
+            Some(payload) => std::panic::resume_unwind(payload),
+            None => panic!("job was cancelled"),
+        }
+    }
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_cancelled() {
+            write!(f, "Job Error: job was cancelled!")
+        } else {
+            write!(f, "Job Error: job panicked with \"{}\"!", self.message())
+        }
+    }
+}
+
+impl fmt::Debug for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl error::Error for JobError {}
+
+pub(super) struct JobResult<T> {
+    value: Mutex<Option<Result<T, JobError>>>,
+    finished: Condvar,
+}
+
+impl<T> JobResult<T> {
+    pub fn new() -> Self {
+        Self {
+            value: Mutex::new(None),
+            finished: Condvar::new(),
+        }
+    }
+
+    pub fn set(&self, value: Result<T, JobError>) {
+        *self.value.lock().unwrap() = Some(value);
+        self.finished.notify_all();
+    }
+}
+
+/// Sends a job's result to its handle, cancelling the handle if it is dropped without sending
+pub(super) struct ResultSender<T> {
+    result: Option<Arc<JobResult<T>>>,
+}
+
+impl<T> ResultSender<T> {
+    pub fn new(result: Arc<JobResult<T>>) -> Self {
+        Self {
+            result: Some(result),
+        }
+    }
+
+    pub fn send(mut self, value: Result<T, JobError>) {
+        if let Some(result) = self.result.take() {
+            result.set(value);
+        }
+    }
+}
+
+impl<T> Drop for ResultSender<T> {
+    fn drop(&mut self) {
+        if let Some(result) = self.result.take() {
+            result.set(Err(JobError::cancelled()));
+        }
+    }
+}
+
+/// A handle to a job queued with ThreadPool::execute()
+///
+/// Dropping the handle does not cancel the job
+pub struct JobHandle<T> {
+    result: Arc<JobResult<T>>,
+}
+
+impl<T> JobHandle<T> {
+    pub(super) fn new(result: Arc<JobResult<T>>) -> Self {
+        Self { result }
+    }
+
+    /// Checks if the job has finished running
+    pub fn is_finished(&self) -> bool {
+        self.result.value.lock().unwrap().is_some()
+    }
+
+    /// Blocks until the job has finished running
+    ///
+    /// Calling this from a job running on the same pool can block forever
+    pub fn wait(&self) {
+        let mut value = self.result.value.lock().unwrap();
+
+        while value.is_none() {
+            value = self.result.finished.wait(value).unwrap();
+        }
+    }
+
+    /// Blocks until the job has finished and returns its result, or an error if it panicked
+    pub fn join(self) -> Result<T, JobError> {
+        self.wait();
+
+        self.result
+            .value
+            .lock()
+            .unwrap()
+            .take()
+            .expect("Failed to get job result after waiting for it!")
+    }
+}
+
+/// A set of jobs that can be waited on together
+pub struct JobGroup<T> {
+    handles: Vec<JobHandle<T>>,
+}
+
+impl<T> JobGroup<T> {
+    pub fn new() -> Self {
+        Self {
+            handles: Vec::new(),
+        }
+    }
+
+    /// Adds a job to the group
+    pub fn push(&mut self, handle: JobHandle<T>) {
+        self.handles.push(handle);
+    }
+
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Checks if every job in the group has finished running
+    pub fn is_finished(&self) -> bool {
+        self.handles.iter().all(JobHandle::is_finished)
+    }
+
+    /// Blocks until every job in the group has finished running
+    pub fn wait(&self) {
+        self.handles.iter().for_each(JobHandle::wait);
+    }
+
+    /// Blocks until every job in the group has finished and returns their results in the order they were added
+    pub fn join(self) -> Vec<Result<T, JobError>> {
+        self.handles.into_iter().map(JobHandle::join).collect()
+    }
+}
+
+impl<T> Default for JobGroup<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<JobHandle<T>> for JobGroup<T> {
+    fn from_iter<I: IntoIterator<Item = JobHandle<T>>>(iter: I) -> Self {
+        Self {
+            handles: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use super::*;
+    use crate::thread_pool::ThreadPool;
+
+    #[test]
+    fn join_returns_the_result() {
+        let pool = ThreadPool::new(2);
+
+        let handle = pool.execute(|| 6 * 7);
+        assert_eq!(handle.join().unwrap(), 42);
+    }
+
+    #[test]
+    fn join_returns_panics() {
+        let pool = ThreadPool::new(2);
+
+        let err = pool.execute(|| panic!("job failed")).join().unwrap_err();
+        assert!(!err.is_cancelled());
+        assert_eq!(err.message(), "job failed");
+        assert_eq!(
+            err.to_string(),
+            "Job Error: job panicked with \"job failed\"!"
+        );
+
+        let code = 7;
+        let err = pool
+            .execute(move || panic!("job failed with {code}"))
+            .join()
+            .unwrap_err();
+        assert_eq!(err.message(), "job failed with 7");
+
+        // the pool keeps running jobs after one panicked
+        assert_eq!(pool.execute(|| 1).join().unwrap(), 1);
+    }
+
+    #[test]
+    fn resume_continues_the_panic() {
+        let pool = ThreadPool::new(1);
+        let err = pool.execute(|| panic!("job failed")).join().unwrap_err();
+
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| err.resume())).unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"job failed"));
+    }
+
+    #[test]
+    fn dropping_the_sender_cancels_the_handle() {
+        let result = Arc::new(JobResult::<u32>::new());
+        let handle = JobHandle::new(Arc::clone(&result));
+        assert!(!handle.is_finished());
+
+        drop(ResultSender::new(result));
+        assert!(handle.is_finished());
+        assert!(handle.join().unwrap_err().is_cancelled());
+    }
+
+    #[test]
+    fn groups_join_in_the_order_jobs_were_added() {
+        let pool = ThreadPool::new(4);
+
+        let group = (0..16)
+            .map(|i| {
+                pool.execute(move || match i {
+                    5 => panic!("job 5 failed"),
+                    _ => i * 2,
+                })
+            })
+            .collect::<JobGroup<_>>();
+        assert_eq!(group.len(), 16);
+
+        group.wait();
+        assert!(group.is_finished());
+
+        let results = group.join();
+        assert_eq!(results[5].as_ref().unwrap_err().message(), "job 5 failed");
+        for (i, result) in results.iter().enumerate().filter(|(i, _)| *i != 5) {
+            assert_eq!(*result.as_ref().unwrap(), i * 2);
+        }
+    }
+}
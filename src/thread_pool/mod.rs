@@ -1,128 +1,509 @@
 use std::{
+    cell::Cell,
     collections::VecDeque,
-    sync::{Arc, Condvar, Mutex},
+    fmt,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Condvar, Mutex, OnceLock,
+    },
     thread,
+    time::{Duration, Instant},
 };
 
+use crate::log::{Level, Logger};
+
+mod job;
+mod scope;
+mod task;
+
+pub use job::{JobError, JobGroup, JobHandle};
+use job::{JobResult, ResultSender};
+pub use scope::Scope;
+pub use task::Task;
+use task::TaskCell;
+
 type Job = Box<dyn FnOnce() + Send + 'static>;
+
+thread_local! {
+    /// The pool and deque index of the worker running on this thread, if any
+    static WORKER: Cell<Option<(usize, usize)>> = const { Cell::new(None) };
+}
+
+/// What happens to queued jobs when a thread pool is dropped
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ShutdownPolicy {
+    /// Every queued job is run before the workers stop
+    #[default]
+    Drain,
+    /// Queued jobs are dropped without running, jobs that already started still finish
+    Cancel,
+}
+
+/// A work stealing thread pool
+///
+/// Every worker has its own deque of jobs, jobs spawned from a worker go onto that
+/// worker's deque and jobs spawned from anywhere else go onto a shared queue.
+/// Idle workers take jobs from the shared queue and steal jobs from other workers
+///
+/// Dropping the pool stops and joins its workers, handling queued jobs
+/// according to its ShutdownPolicy
 pub struct ThreadPool {
-    job_queue: Arc<JobQueue>,
-    pool: Box<[(Arc<ThreadLock>, thread::JoinHandle<()>)]>,
+    shared: Arc<Shared>,
+    pool: Box<[thread::JoinHandle<()>]>,
+    shutdown_policy: ShutdownPolicy,
 }
 
 impl ThreadPool {
     pub fn new(thread_count: usize) -> Self {
+        Self::with_shutdown_policy(thread_count, ShutdownPolicy::default())
+    }
+
+    pub fn with_shutdown_policy(thread_count: usize, shutdown_policy: ShutdownPolicy) -> Self {
         assert!(thread_count > 0);
         let mut thread_pool_vec = Vec::with_capacity(thread_count);
 
-        let job_queue = Arc::new(JobQueue::new());
+        let shared = Arc::new(Shared::new(thread_count));
 
-        for _ in 0..thread_count {
-            let thread_lock = Arc::new(ThreadLock::new());
+        for index in 0..thread_count {
+            let shared_handle = Arc::clone(&shared);
 
-            let queue_handle = Arc::clone(&job_queue);
-            let thread_lock_handle = Arc::clone(&thread_lock);
+            let thread_handle = thread::spawn(move || {
+                WORKER.set(Some((shared_handle.id(), index)));
 
-            let thread_handle = thread::spawn(move || loop {
-                // dont do anything unless the job queue threadlock is blocked
-                let job = queue_handle.get_job();
-                thread_lock_handle.block();
-                if let Some(job) = job {
-                    job();
+                while let Some(job) = shared_handle.wait_for_job(Some(index)) {
+                    let start = Instant::now();
+                    shared_handle.run_job(job);
+                    shared_handle
+                        .busy_nanos
+                        .fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
                 }
-                thread_lock_handle.unblock();
             });
 
-            thread_pool_vec.push((thread_lock, thread_handle));
+            thread_pool_vec.push(thread_handle);
         }
 
         Self {
-            job_queue,
+            shared,
             pool: thread_pool_vec.into_boxed_slice(),
+            shutdown_policy,
         }
     }
 
-    pub fn execute<F: FnOnce() + Send + 'static>(&mut self, job: F) {
-        self.job_queue.assign_job(Box::new(job));
+    /// The number of worker threads in the pool
+    pub fn thread_count(&self) -> usize {
+        self.pool.len()
+    }
+
+    pub fn shutdown_policy(&self) -> ShutdownPolicy {
+        self.shutdown_policy
     }
 
-    /// blocks the current thread until all the currently queued jobs are finished
-    pub fn wait(&self) {
-        self.job_queue.wait_for_clear();
-        for (lock, _) in self.pool.iter() {
-            lock.wait();
+    /// Sets the logger pool events are reported to, the logger can only be set once
+    pub fn set_logger(&self, logger: Arc<Logger>) {
+        let _ = self.shared.logger.set(logger);
+    }
+
+    /// The total time the workers have spent running jobs, summed over every worker
+    pub fn busy_time(&self) -> Duration {
+        Duration::from_nanos(self.shared.busy_nanos.load(Ordering::Relaxed))
+    }
+
+    /// Queues a job on the pool and returns a handle to its result
+    pub fn execute<T, F>(&self, job: F) -> JobHandle<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let result = Arc::new(JobResult::new());
+        // if the job is cancelled the sender is dropped without sending, which cancels the handle
+        let sender = ResultSender::new(Arc::clone(&result));
+
+        self.shared.push_job(Box::new(move || {
+            let value = panic::catch_unwind(AssertUnwindSafe(job)).map_err(JobError::panicked);
+            sender.send(value);
+        }));
+
+        JobHandle::new(result)
+    }
+
+    /// Runs a future on the pool, polling it on a worker every time it is woken
+    pub fn spawn_task<F>(&self, future: F) -> Task<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (cell, task) = TaskCell::new(&self.shared, future);
+        cell.schedule();
+        task
+    }
+
+    /// Runs a function for every item, splitting the items into one chunk per worker
+    ///
+    /// Blocks until every item has been processed, returns an error if f panicked
+    pub fn par_for_each<T: Sync, F: Fn(&T) + Sync>(
+        &self,
+        items: &[T],
+        f: F,
+    ) -> Result<(), JobError> {
+        let chunk_size = items.len().div_ceil(self.thread_count()).max(1);
+        let f = &f;
+
+        self.scope(|s| {
+            for chunk in items.chunks(chunk_size) {
+                s.spawn(move || chunk.iter().for_each(f));
+            }
+        })
+    }
+
+    /// Runs a function that can spawn jobs borrowing data from outside the scope
+    ///
+    /// Blocks until every job spawned in the scope has finished, the calling thread
+    /// runs queued jobs while it waits. If any of the jobs panicked, the first panic
+    /// is returned as an error once the scope has finished
+    pub fn scope<'env, F, R>(&self, f: F) -> Result<R, JobError>
+    where
+        F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> R,
+    {
+        let scope = Scope::new(Arc::clone(&self.shared));
+
+        // the scope must wait for its jobs even if f panics, since they may borrow its stack
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+        scope.wait();
+
+        match result {
+            Ok(result) => scope.take_panic().map_or(Ok(result), Err),
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        self.shared.log(
+            Level::Debug,
+            format_args!("shutting down with policy {:?}", self.shutdown_policy),
+        );
+
+        if self.shutdown_policy == ShutdownPolicy::Cancel {
+            let cancelled = self.shared.cancel_jobs();
+            self.shared.log(
+                Level::Debug,
+                format_args!("cancelled {cancelled} queued jobs"),
+            );
+        }
+
+        self.shared.state.lock().unwrap().is_shutting_down = true;
+        self.shared.has_job.notify_all();
+
+        let current_worker = self.shared.current_worker();
+
+        for (index, handle) in std::mem::take(&mut self.pool)
+            .into_vec()
+            .into_iter()
+            .enumerate()
+        {
+            // a job dropping the pool can't wait for itself, that worker stops once the job returns
+            if Some(index) != current_worker && handle.join().is_err() {
+                self.shared
+                    .log(Level::Error, format_args!("worker {index} panicked"));
+            }
         }
     }
 }
 
-struct ThreadLock {
-    blocking: Mutex<bool>,
-    cvar: Condvar,
+struct QueueState {
+    /// jobs sitting in a queue
+    queued: usize,
+    is_shutting_down: bool,
+}
+
+struct Shared {
+    injector: Mutex<VecDeque<Job>>,
+    deques: Box<[Mutex<VecDeque<Job>>]>,
+    state: Mutex<QueueState>,
+    has_job: Condvar,
+    /// total time workers have spent running jobs
+    busy_nanos: AtomicU64,
+    logger: OnceLock<Arc<Logger>>,
 }
 
-impl ThreadLock {
-    pub fn new() -> Self {
+impl Shared {
+    fn new(thread_count: usize) -> Self {
         Self {
-            blocking: Mutex::new(false),
-            cvar: Condvar::new(),
+            injector: Mutex::new(VecDeque::new()),
+            deques: (0..thread_count)
+                .map(|_| Mutex::new(VecDeque::new()))
+                .collect(),
+            state: Mutex::new(QueueState {
+                queued: 0,
+                is_shutting_down: false,
+            }),
+            has_job: Condvar::new(),
+            busy_nanos: AtomicU64::new(0),
+            logger: OnceLock::new(),
         }
     }
 
-    pub fn block(&self) {
-        *self.blocking.lock().unwrap() = true;
+    /// Identifies the pool so workers of other pools use the shared queue
+    fn id(&self) -> usize {
+        std::ptr::from_ref(self) as usize
     }
 
-    pub fn unblock(&self) {
-        *self.blocking.lock().unwrap() = false;
-        self.cvar.notify_one();
+    /// The index of this thread's deque, if this thread is a worker of this pool
+    fn current_worker(&self) -> Option<usize> {
+        WORKER
+            .get()
+            .and_then(|(pool, index)| (pool == self.id()).then_some(index))
     }
 
-    pub fn wait(&self) {
-        let mut is_blocking = self.blocking.lock().unwrap();
+    fn push_job(&self, job: Job) {
+        // counted before pushing so a worker can never take a job that hasn't been counted
+        self.state.lock().unwrap().queued += 1;
 
-        while *is_blocking {
-            is_blocking = self.cvar.wait(is_blocking).unwrap();
+        match self.current_worker() {
+            Some(index) => self.deques[index].lock().unwrap().push_back(job),
+            None => self.injector.lock().unwrap().push_back(job),
         }
+
+        self.has_job.notify_one();
     }
-}
 
-struct JobQueue {
-    queue: Mutex<VecDeque<Job>>,
-    is_empty: ThreadLock,
-    has_task: ThreadLock,
-}
+    /// Takes a job from the worker's own deque, then the shared queue, then other workers
+    fn find_job(&self, worker: Option<usize>) -> Option<Job> {
+        let own_job = worker.and_then(|index| self.deques[index].lock().unwrap().pop_back());
 
-impl JobQueue {
-    pub fn new() -> Self {
-        let has_task = ThreadLock::new();
-        has_task.block();
-        Self {
-            queue: Mutex::new(VecDeque::new()),
-            is_empty: ThreadLock::new(),
-            has_task,
+        let job = own_job
+            .or_else(|| self.injector.lock().unwrap().pop_front())
+            .or_else(|| {
+                let start = worker.map_or(0, |index| index + 1);
+
+                (0..self.deques.len())
+                    .map(|offset| (start + offset) % self.deques.len())
+                    .filter(|&index| Some(index) != worker)
+                    .find_map(|index| self.deques[index].lock().unwrap().pop_front())
+            });
+
+        if job.is_some() {
+            self.state.lock().unwrap().queued -= 1;
+        }
+
+        job
+    }
+
+    /// Blocks until a job can be taken, returns None once the pool is
+    /// shutting down and there are no jobs left
+    fn wait_for_job(&self, worker: Option<usize>) -> Option<Job> {
+        loop {
+            if let Some(job) = self.find_job(worker) {
+                return Some(job);
+            }
+
+            let mut state = self.state.lock().unwrap();
+            while state.queued == 0 {
+                if state.is_shutting_down {
+                    return None;
+                }
+                state = self.has_job.wait(state).unwrap();
+            }
         }
     }
 
-    pub fn assign_job(&self, job: Job) {
-        self.queue.lock().unwrap().push_back(job);
-        self.is_empty.block();
-        self.has_task.unblock();
+    /// Drops every queued job without running it, returning how many were dropped
+    fn cancel_jobs(&self) -> usize {
+        let mut cancelled = std::mem::take(&mut *self.injector.lock().unwrap());
+
+        for deque in self.deques.iter() {
+            cancelled.append(&mut deque.lock().unwrap());
+        }
+
+        let cancelled_count = cancelled.len();
+        self.state.lock().unwrap().queued -= cancelled_count;
+        // dropped outside of the locks since dropping a job can queue its result
+        drop(cancelled);
+
+        cancelled_count
     }
 
-    pub fn get_job(&self) -> Option<Job> {
-        self.has_task.wait();
-        let mut queue = self.queue.lock().unwrap();
-        if let Some(job) = queue.pop_front() {
-            self.has_task.unblock();
-            Some(job)
-        } else {
-            self.is_empty.unblock();
-            self.has_task.block();
-            None
+    fn run_job(&self, job: Job) {
+        // a panicking job shouldn't take the worker down with it
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(job)) {
+            self.log(
+                Level::Warn,
+                format_args!("job panicked: {}", JobError::panicked(payload).message()),
+            );
         }
     }
 
-    pub fn wait_for_clear(&self) {
-        self.is_empty.wait();
+    fn log(&self, level: Level, args: fmt::Arguments) {
+        if let Some(logger) = self.logger.get() {
+            logger.log(level, "thread_pool", args);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            mpsc, Barrier,
+        },
+        thread,
+    };
+
+    use super::*;
+
+    #[test]
+    fn scoped_jobs_borrow_from_the_stack() {
+        let pool = ThreadPool::new(4);
+        let items = (1..=100).collect::<Vec<usize>>();
+        let sum = AtomicUsize::new(0);
+
+        pool.scope(|s| {
+            for chunk in items.chunks(10) {
+                let sum = &sum;
+                s.spawn(move || {
+                    sum.fetch_add(chunk.iter().sum(), Ordering::Relaxed);
+                });
+            }
+        })
+        .unwrap();
+
+        assert_eq!(sum.into_inner(), 5050);
+    }
+
+    #[test]
+    fn par_for_each_visits_every_item() {
+        let pool = ThreadPool::new(3);
+        let items = (0..1000).collect::<Vec<usize>>();
+        let visited = AtomicUsize::new(0);
+
+        pool.par_for_each(&items, |_| {
+            visited.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+
+        assert_eq!(visited.into_inner(), items.len());
+    }
+
+    #[test]
+    fn jobs_queued_by_a_worker_are_stolen() {
+        let pool = ThreadPool::new(4);
+        // every job blocks until all four run at once, which only happens if the
+        // other workers steal them from the deque of the worker that queued them
+        let barrier = Barrier::new(4);
+
+        pool.scope(|s| {
+            let barrier = &barrier;
+            s.spawn(move || {
+                for _ in 0..4 {
+                    s.spawn(move || {
+                        barrier.wait();
+                    });
+                }
+            });
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn scope_returns_the_first_panic_after_every_job_finished() {
+        let pool = ThreadPool::new(2);
+        let finished = AtomicUsize::new(0);
+
+        let result = pool.scope(|s| {
+            s.spawn(|| panic!("scoped job failed"));
+            for _ in 0..8 {
+                s.spawn(|| {
+                    finished.fetch_add(1, Ordering::Relaxed);
+                });
+            }
+        });
+
+        assert_eq!(result.unwrap_err().message(), "scoped job failed");
+        assert_eq!(finished.into_inner(), 8);
+    }
+
+    /// Occupies the only worker of a pool until the check passes, so jobs queued meanwhile
+    /// are still queued when the pool is dropped
+    fn block_worker<F>(pool: &ThreadPool, until: F) -> JobHandle<()>
+    where
+        F: Fn() -> bool + Send + 'static,
+    {
+        let (started, has_started) = mpsc::channel();
+        let handle = pool.execute(move || {
+            started.send(()).unwrap();
+            while !until() {
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+
+        has_started.recv().unwrap();
+        handle
+    }
+
+    #[test]
+    fn drain_runs_queued_jobs_before_stopping() {
+        let pool = ThreadPool::with_shutdown_policy(1, ShutdownPolicy::Drain);
+        let is_queued = Arc::new(AtomicBool::new(false));
+        let ran = Arc::new(AtomicUsize::new(0));
+
+        let blocker = block_worker(&pool, {
+            let is_queued = Arc::clone(&is_queued);
+            move || is_queued.load(Ordering::Relaxed)
+        });
+        let handles = (0..8)
+            .map(|_| {
+                let ran = Arc::clone(&ran);
+                pool.execute(move || {
+                    ran.fetch_add(1, Ordering::Relaxed);
+                })
+            })
+            .collect::<JobGroup<_>>();
+
+        is_queued.store(true, Ordering::Relaxed);
+        drop(pool);
+
+        assert!(blocker.join().is_ok());
+        assert!(handles.join().iter().all(Result::is_ok));
+        assert_eq!(ran.load(Ordering::Relaxed), 8);
+    }
+
+    #[test]
+    fn cancel_drops_queued_jobs() {
+        let pool = ThreadPool::with_shutdown_policy(1, ShutdownPolicy::Cancel);
+        let last_job = Arc::new(Mutex::new(None::<JobHandle<()>>));
+        let ran = Arc::new(AtomicUsize::new(0));
+
+        // the worker is released once the pool has cancelled the last job
+        let blocker = block_worker(&pool, {
+            let last_job = Arc::clone(&last_job);
+            move || {
+                let last_job = last_job.lock().unwrap();
+                last_job.as_ref().is_some_and(JobHandle::is_finished)
+            }
+        });
+        let mut handles = (0..8)
+            .map(|_| {
+                let ran = Arc::clone(&ran);
+                pool.execute(move || {
+                    ran.fetch_add(1, Ordering::Relaxed);
+                })
+            })
+            .collect::<Vec<_>>();
+
+        *last_job.lock().unwrap() = handles.pop();
+        drop(pool);
+
+        // the blocker had already started, so it still finished
+        assert!(blocker.join().is_ok());
+        assert_eq!(ran.load(Ordering::Relaxed), 0);
+        for handle in handles {
+            assert!(handle.join().unwrap_err().is_cancelled());
+        }
+        let last_job = last_job.lock().unwrap().take().unwrap();
+        assert!(last_job.join().unwrap_err().is_cancelled());
     }
 }
@@ -0,0 +1,96 @@
+use std::{
+    any::Any,
+    marker::PhantomData,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Condvar, Mutex},
+};
+
+use super::{Job, JobError, Shared};
+
+/// A scope to spawn jobs that can borrow data living longer than the scope
+///
+/// Created with ThreadPool::scope()
+pub struct Scope<'scope, 'env: 'scope> {
+    shared: Arc<Shared>,
+    state: Arc<ScopeState>,
+    // invariant lifetimes, the same way std::thread::Scope does it
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+struct ScopeState {
+    unfinished: Mutex<usize>,
+    finished: Condvar,
+    panic: Mutex<Option<Box<dyn Any + Send>>>,
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    pub(super) fn new(shared: Arc<Shared>) -> Self {
+        Self {
+            shared,
+            state: Arc::new(ScopeState {
+                unfinished: Mutex::new(0),
+                finished: Condvar::new(),
+                panic: Mutex::new(None),
+            }),
+            scope: PhantomData,
+            env: PhantomData,
+        }
+    }
+
+    /// Queues a job on the pool, the scope will not end until it has finished
+    pub fn spawn<F: FnOnce() + Send + 'scope>(&'scope self, job: F) {
+        *self.state.unfinished.lock().unwrap() += 1;
+
+        let state = Arc::clone(&self.state);
+
+        let job: Box<dyn FnOnce() + Send + 'scope> = Box::new(move || {
+            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(job)) {
+                state.panic.lock().unwrap().get_or_insert(payload);
+            }
+
+            let mut unfinished = state.unfinished.lock().unwrap();
+            *unfinished -= 1;
+            if *unfinished == 0 {
+                state.finished.notify_all();
+            }
+        });
+
+        // SAFETY: ThreadPool::scope() does not return until every job spawned
+        // in the scope has finished, so nothing the job borrows can be dropped while it runs
+        let job = unsafe { std::mem::transmute::<Box<dyn FnOnce() + Send + 'scope>, Job>(job) };
+
+        self.shared.push_job(job);
+    }
+
+    /// Blocks until every job in the scope has finished, running queued jobs in the meantime
+    pub(super) fn wait(&self) {
+        let worker = self.shared.current_worker();
+
+        loop {
+            if let Some(job) = self.shared.find_job(worker) {
+                self.shared.run_job(job);
+                continue;
+            }
+
+            let unfinished = self.state.unfinished.lock().unwrap();
+            if *unfinished == 0 {
+                break;
+            }
+
+            // woken once the last of the scope's jobs finishes, until then the jobs still
+            // running are on other workers, which also run any jobs they queue
+            drop(self.state.finished.wait(unfinished).unwrap());
+        }
+    }
+
+    /// Takes the first panic that happened in one of the scope's jobs
+    pub(super) fn take_panic(&self) -> Option<JobError> {
+        self.state
+            .panic
+            .lock()
+            .unwrap()
+            .take()
+            .map(JobError::panicked)
+    }
+}
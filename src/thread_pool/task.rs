@@ -0,0 +1,221 @@
+use std::{
+    future::{self, Future},
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, Weak,
+    },
+    task::{Context, Poll, Wake, Waker},
+};
+
+use super::{JobError, JobHandle, JobResult, ResultSender, Shared};
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// A handle to an async task spawned with ThreadPool::spawn_task()
+///
+/// Systems can check on the task every frame with Task::try_join() instead of blocking
+pub struct Task<T> {
+    handle: Option<JobHandle<T>>,
+}
+
+impl<T> Task<T> {
+    /// Checks if the task has finished, successfully or not
+    pub fn is_finished(&self) -> bool {
+        self.handle.as_ref().is_none_or(JobHandle::is_finished)
+    }
+
+    /// Takes the task's result if it has finished
+    ///
+    /// Returns None if the task is still running or its result has already been taken
+    pub fn try_join(&mut self) -> Option<Result<T, JobError>> {
+        if self.handle.as_ref()?.is_finished() {
+            self.handle.take().map(JobHandle::join)
+        } else {
+            None
+        }
+    }
+
+    /// Blocks until the task has finished and returns its result
+    ///
+    /// Panics if the result was already taken with Task::try_join()
+    pub fn join(self) -> Result<T, JobError> {
+        self.handle
+            .expect("Failed to join task whose result was already taken!")
+            .join()
+    }
+}
+
+/// A spawned future and the pool it gets polled on
+pub(super) struct TaskCell {
+    future: Mutex<Option<BoxFuture>>,
+    is_scheduled: AtomicBool,
+    /// Weak so a task that is never woken again doesn't keep the pool's queues alive
+    shared: Weak<Shared>,
+}
+
+impl TaskCell {
+    /// Creates a task for a future, the returned task still needs to be scheduled
+    pub fn new<F>(shared: &Arc<Shared>, future: F) -> (Arc<Self>, Task<F::Output>)
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let result = Arc::new(JobResult::new());
+        // if the task is dropped before finishing, its handle is cancelled
+        let mut sender = Some(ResultSender::new(Arc::clone(&result)));
+        let mut future = Box::pin(future);
+
+        let future = future::poll_fn(move |cx| {
+            let value = match panic::catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx))) {
+                Ok(Poll::Pending) => return Poll::Pending,
+                Ok(Poll::Ready(value)) => Ok(value),
+                Err(payload) => Err(JobError::panicked(payload)),
+            };
+
+            if let Some(sender) = sender.take() {
+                sender.send(value);
+            }
+            Poll::Ready(())
+        });
+
+        let cell = Arc::new(Self {
+            future: Mutex::new(Some(Box::pin(future))),
+            is_scheduled: AtomicBool::new(false),
+            shared: Arc::downgrade(shared),
+        });
+
+        let task = Task {
+            handle: Some(JobHandle::new(result)),
+        };
+
+        (cell, task)
+    }
+
+    /// Queues the task to be polled, unless it is already queued
+    pub fn schedule(self: &Arc<Self>) {
+        if self.is_scheduled.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        // once the pool is gone the task can never finish, dropping it cancels its handle
+        if let Some(shared) = self.shared.upgrade() {
+            let cell = Arc::clone(self);
+            shared.push_job(Box::new(move || cell.poll()));
+        }
+    }
+
+    fn poll(self: Arc<Self>) {
+        // cleared before polling so a wake during the poll queues the task again
+        self.is_scheduled.store(false, Ordering::Release);
+
+        let waker = Waker::from(Arc::clone(&self));
+        let mut cx = Context::from_waker(&waker);
+
+        let mut future = self.future.lock().unwrap();
+        if let Some(pending) = future.as_mut() {
+            if pending.as_mut().poll(&mut cx).is_ready() {
+                *future = None;
+            }
+        }
+    }
+}
+
+impl Wake for TaskCell {
+    fn wake(self: Arc<Self>) {
+        self.schedule();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.schedule();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::*;
+    use crate::thread_pool::ThreadPool;
+
+    /// A value sent to a future from outside of the pool
+    #[derive(Default)]
+    struct Slot {
+        value: Option<u32>,
+        waker: Option<Waker>,
+    }
+
+    fn receive(slot: Arc<Mutex<Slot>>) -> impl Future<Output = u32> {
+        future::poll_fn(move |cx| {
+            let mut slot = slot.lock().unwrap();
+            match slot.value.take() {
+                Some(value) => Poll::Ready(value),
+                None => {
+                    slot.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        })
+    }
+
+    /// Waits until the task has finished and takes its result
+    fn wait_for<T>(task: &mut Task<T>) -> Result<T, JobError> {
+        loop {
+            if let Some(result) = task.try_join() {
+                return result;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn try_join_takes_the_result_once_finished() {
+        let pool = ThreadPool::new(2);
+        let slot = Arc::new(Mutex::new(Slot::default()));
+
+        let mut task = pool.spawn_task(receive(Arc::clone(&slot)));
+        // the task has been polled once it stored its waker
+        while slot.lock().unwrap().waker.is_none() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!(!task.is_finished());
+        assert!(task.try_join().is_none());
+
+        let waker = {
+            let mut slot = slot.lock().unwrap();
+            slot.value = Some(42);
+            slot.waker.take().unwrap()
+        };
+        waker.wake();
+
+        assert_eq!(wait_for(&mut task).unwrap(), 42);
+        assert!(task.is_finished());
+        assert!(task.try_join().is_none());
+    }
+
+    #[test]
+    fn join_returns_panics() {
+        let pool = ThreadPool::new(1);
+
+        let task = pool.spawn_task(async { panic!("task failed") });
+        assert_eq!(task.join().unwrap_err().message(), "task failed");
+    }
+
+    #[test]
+    fn tasks_that_can_never_finish_are_cancelled() {
+        let pool = ThreadPool::new(1);
+        let slot = Arc::new(Mutex::new(Slot::default()));
+
+        let mut task = pool.spawn_task(receive(Arc::clone(&slot)));
+        while slot.lock().unwrap().waker.is_none() {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        // the waker is the last thing keeping the pending future alive
+        drop(pool);
+        slot.lock().unwrap().waker.take().unwrap().wake();
+
+        assert!(wait_for(&mut task).unwrap_err().is_cancelled());
+    }
+}
@@ -1,16 +1,21 @@
+mod schedule;
+
 use std::time;
 
-/// Executes a given function at most once in the given time interval
+pub(crate) use schedule::Timers;
+pub use schedule::{Timer, TimerHandle, TimerInterval};
+
+/// Counts how many times a given time interval has passed
 ///
-/// The timer will execute slower than that interval
-/// if the time between calls to Timer::tick() is longer than that interval
-pub struct Timer {
+/// The timer will execute slower than that interval if the time between
+/// calls to IntervalTimer::ticks() is longer than the interval times the max tick count
+pub struct IntervalTimer {
     time_of_last_execution: time::Instant,
     interval: time::Duration,
     is_first_execution: bool,
 }
 
-impl Timer {
+impl IntervalTimer {
     pub fn new(interval: time::Duration) -> Self {
         Self {
             // this ensures that tick executes its function
@@ -27,42 +32,47 @@ impl Timer {
         self.time_of_last_execution = time::Instant::now();
     }
 
-    /// Ticks the timer, executes the given function if the interval has been reached
-    /// it is recommended to call Timer::reset() right before the first call to tick
-    /// outside of the loop if its in one, which would look like this
+    /// Ticks the timer, returning how many intervals have passed since the last tick
+    /// that returned a non zero count, at most max_ticks
     ///
-    /// ```
+    /// Any intervals past max_ticks are dropped, so a long stall doesn't cause a
+    /// burst of executions afterwards. It is recommended to call IntervalTimer::reset() right
+    /// before the first call to ticks outside of the loop if its in one, which would look like this
+    ///
+    /// ```ignore
     /// fn timer_example() {
     ///     // a timer that executes at most once every 10 milliseconds
-    ///     let mut timer = Timer::new(Duration::from_millis(10))
+    ///     let mut timer = IntervalTimer::new(Duration::from_millis(10));
     ///
     ///     timer.reset();
     ///     loop {
-    ///         let should_execute = timer.tick();
-    ///
-    ///         if should_execute {
-    ///             println!("Timer Tick!") // prints "Timer Tick!" at most every 10ms
+    ///         // catches up on at most 3 missed intervals
+    ///         for _ in 0..timer.ticks(3) {
+    ///             println!("Timer Tick!") // prints "Timer Tick!" every 10ms on average
     ///         }
     ///     }
     /// }
     /// ```
-    pub fn tick(&mut self) -> bool {
+    pub fn ticks(&mut self, max_ticks: u32) -> u32 {
         if self.is_first_execution {
             self.time_of_last_execution = time::Instant::now();
             self.is_first_execution = false;
-            true
+            1.min(max_ticks)
         } else {
             let now = time::Instant::now();
             let elapsed = now.duration_since(self.time_of_last_execution);
 
-            if elapsed >= self.interval {
-                // This takes into account starting the execution late and adjusts the last execution
-                // time accordingly to try to be more accurate
-                self.time_of_last_execution = now - (elapsed - self.interval);
+            let due = (elapsed.as_secs_f64() / self.interval.as_secs_f64()) as u64;
 
-                true
+            if due > max_ticks as u64 {
+                // too far behind to catch up, start counting again from now
+                self.time_of_last_execution = now;
+                max_ticks
             } else {
-                false
+                // This takes into account starting the execution late and adjusts the last execution
+                // time accordingly to try to be more accurate
+                self.time_of_last_execution += self.interval * due as u32;
+                due as u32
             }
         }
     }
@@ -0,0 +1,419 @@
+use std::{
+    fmt,
+    ops::Sub,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use crate::{ecs, Engine, Entity, ErrorAction};
+
+/// Runs when a timer fires, with the entity the timer is attached to
+type Callback = Box<dyn FnMut(Arc<Engine>, Option<&Entity>) -> Result<(), ecs::Error> + Send>;
+
+/// How long a timer waits before it fires
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TimerInterval {
+    /// Time passed in the dt of the scene's frames
+    Time(Duration),
+    /// Physics frames run by the scene
+    PhysicsSteps(u32),
+}
+
+/// A timer that fires an event, runs a callback or both once its interval has passed
+///
+/// Timers are started in a scene with SceneState::start_timer(), or attached to an entity
+/// with SceneState::start_entity_timer()
+pub struct Timer {
+    interval: TimerInterval,
+    is_repeating: bool,
+    event: Option<String>,
+    callback: Option<Callback>,
+}
+
+impl Timer {
+    /// A timer that fires once the duration has passed
+    pub fn after(duration: Duration) -> Self {
+        Self::new(TimerInterval::Time(duration))
+    }
+
+    /// A timer that fires once the scene has run the number of physics frames
+    pub fn after_physics_steps(steps: u32) -> Self {
+        Self::new(TimerInterval::PhysicsSteps(steps))
+    }
+
+    /// A timer that fires once the interval has passed
+    pub fn new(interval: TimerInterval) -> Self {
+        Self {
+            interval,
+            is_repeating: false,
+            event: None,
+            callback: None,
+        }
+    }
+
+    /// Fires the timer every interval until it is cancelled, instead of only once
+    ///
+    /// A repeating timer with an interval of zero fires once every frame
+    pub fn repeating(mut self) -> Self {
+        self.is_repeating = true;
+        self
+    }
+
+    /// Fires an event every time the timer fires, see SceneState::has_timer_event()
+    pub fn with_event(mut self, event: &str) -> Self {
+        self.event = Some(event.to_owned());
+        self
+    }
+
+    /// Runs a callback every time the timer fires, with the entity the timer is attached to
+    ///
+    /// Errors returned by the callback are passed to the engine's error handler,
+    /// ErrorAction::DisableSystem cancels the timer
+    pub fn with_callback<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(Arc<Engine>, Option<&Entity>) -> Result<(), ecs::Error> + Send + 'static,
+    {
+        self.callback = Some(Box::new(callback));
+        self
+    }
+
+    pub fn interval(&self) -> TimerInterval {
+        self.interval
+    }
+
+    pub fn is_repeating(&self) -> bool {
+        self.is_repeating
+    }
+}
+
+impl fmt::Debug for Timer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timer")
+            .field("interval", &self.interval)
+            .field("is_repeating", &self.is_repeating)
+            .field("event", &self.event)
+            .field("has_callback", &self.callback.is_some())
+            .finish()
+    }
+}
+
+/// A handle to a started timer, unique across every scene
+///
+/// Timers attached to an entity keep their handle when the entity moves to another scene
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TimerHandle(u64);
+
+/// The next timer handle, shared by every scene so handles from different scenes never match
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(0);
+
+/// Shared with the Firing while it runs, so the timer can be moved or cancelled meanwhile
+type SharedCallback = Arc<Mutex<Callback>>;
+
+/// A timer that has been started in a scene
+struct ScheduledTimer {
+    handle: TimerHandle,
+    entity: Option<Entity>,
+    timer: Timer,
+    /// the timer's callback, taken out of the timer when it was started
+    callback: Option<SharedCallback>,
+    /// how long until the timer fires next, always the same kind of interval as the timer's
+    remaining: TimerInterval,
+    is_paused: bool,
+}
+
+impl ScheduledTimer {
+    /// Counts the timer down, returning how many times it fired and if it has finished
+    fn advance(&mut self, physics_steps: u32, dt: Duration) -> (u32, bool) {
+        let is_repeating = self.timer.is_repeating;
+
+        match (&mut self.remaining, self.timer.interval) {
+            (TimerInterval::Time(remaining), TimerInterval::Time(interval)) => {
+                count_down(remaining, interval, dt, is_repeating)
+            }
+            (TimerInterval::PhysicsSteps(remaining), TimerInterval::PhysicsSteps(interval)) => {
+                count_down(remaining, interval, physics_steps, is_repeating)
+            }
+            _ => (0, false),
+        }
+    }
+}
+
+/// Subtracts what has passed from what remains of an interval, returning how many times
+/// the interval ran out and if a timer that doesn't repeat has finished
+fn count_down<T>(remaining: &mut T, interval: T, mut passed: T, is_repeating: bool) -> (u32, bool)
+where
+    T: Copy + Default + Ord + Sub<Output = T>,
+{
+    let mut count = 0;
+
+    while passed >= *remaining {
+        passed = passed - *remaining;
+        count += 1;
+
+        if !is_repeating {
+            return (count, true);
+        }
+        *remaining = interval;
+
+        if interval == T::default() {
+            return (count, false);
+        }
+    }
+
+    *remaining = *remaining - passed;
+    (count, false)
+}
+
+/// A timer that fired during a frame
+struct Firing {
+    handle: TimerHandle,
+    entity: Option<Entity>,
+    count: u32,
+    event: Option<String>,
+    callback: Option<SharedCallback>,
+}
+
+/// The timers of a scene
+pub(crate) struct Timers {
+    timers: Mutex<Vec<ScheduledTimer>>,
+    /// the events fired this frame, with the entity of the timer that fired them
+    events: Mutex<Vec<(Option<Entity>, String)>>,
+}
+
+impl Timers {
+    pub fn new() -> Self {
+        Self {
+            timers: Mutex::new(Vec::new()),
+            events: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn start(&self, entity: Option<Entity>, mut timer: Timer) -> TimerHandle {
+        let handle = TimerHandle(NEXT_HANDLE.fetch_add(1, Ordering::Relaxed));
+        let callback = timer
+            .callback
+            .take()
+            .map(|callback| Arc::new(Mutex::new(callback)));
+
+        self.timers.lock().unwrap().push(ScheduledTimer {
+            handle,
+            entity,
+            remaining: timer.interval,
+            timer,
+            callback,
+            is_paused: false,
+        });
+
+        handle
+    }
+
+    /// Removes a timer, returning false if it doesn't exist
+    pub fn cancel(&self, handle: TimerHandle) -> bool {
+        let mut timers = self.timers.lock().unwrap();
+
+        let timer_count = timers.len();
+        timers.retain(|scheduled| scheduled.handle != handle);
+        timers.len() != timer_count
+    }
+
+    /// Removes every timer attached to the entity
+    pub fn cancel_entity_timers(&self, entity: &Entity) {
+        self.timers
+            .lock()
+            .unwrap()
+            .retain(|scheduled| scheduled.entity.as_ref() != Some(entity));
+    }
+
+    /// Moves every timer attached to the entity to another scene's timers, attached to the
+    /// entity's handle in that scene
+    ///
+    /// The timers keep their handles and how long they have left
+    pub fn transfer_entity_timers(&self, entity: &Entity, target: &Timers, new_entity: &Entity) {
+        let moved = self
+            .timers
+            .lock()
+            .unwrap()
+            .extract_if(.., |scheduled| scheduled.entity.as_ref() == Some(entity))
+            .map(|mut scheduled| {
+                scheduled.entity = Some(new_entity.clone());
+                scheduled
+            })
+            .collect::<Vec<_>>();
+
+        target.timers.lock().unwrap().extend(moved);
+    }
+
+    /// Pauses or resumes a timer, returning false if it doesn't exist
+    pub fn set_paused(&self, handle: TimerHandle, is_paused: bool) -> bool {
+        let mut timers = self.timers.lock().unwrap();
+
+        match timers
+            .iter_mut()
+            .find(|scheduled| scheduled.handle == handle)
+        {
+            Some(scheduled) => {
+                scheduled.is_paused = is_paused;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remaining(&self, handle: TimerHandle) -> Option<TimerInterval> {
+        let timers = self.timers.lock().unwrap();
+
+        timers
+            .iter()
+            .find(|scheduled| scheduled.handle == handle)
+            .map(|scheduled| scheduled.remaining)
+    }
+
+    /// The events fired this frame by the entity's timers, or the scene's if entity is None
+    pub fn events(&self, entity: Option<&Entity>) -> Vec<String> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(owner, _)| owner.as_ref() == entity)
+            .map(|(_, event)| event.clone())
+            .collect()
+    }
+
+    pub fn has_event(&self, entity: Option<&Entity>, event: &str) -> bool {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(owner, fired)| owner.as_ref() == entity && fired == event)
+    }
+
+    /// Counts every timer down, firing the events and running the callbacks of the
+    /// ones that ran out
+    ///
+    /// Callbacks run without the timers locked, so they can start and cancel timers
+    pub fn advance(&self, engine: &Arc<Engine>, physics_steps: u32, dt: Duration) {
+        self.events.lock().unwrap().clear();
+
+        let mut firings = Vec::new();
+        self.timers.lock().unwrap().retain_mut(|scheduled| {
+            if scheduled.is_paused {
+                return true;
+            }
+
+            let (count, is_finished) = scheduled.advance(physics_steps, dt);
+            if count > 0 {
+                firings.push(Firing {
+                    handle: scheduled.handle,
+                    entity: scheduled.entity.as_ref().map(Entity::clone),
+                    count,
+                    event: scheduled.timer.event.clone(),
+                    callback: scheduled.callback.clone(),
+                });
+            }
+
+            !is_finished
+        });
+
+        for firing in firings {
+            if let Some(event) = &firing.event {
+                let events = (0..firing.count)
+                    .map(|_| (firing.entity.as_ref().map(Entity::clone), event.clone()));
+                self.events.lock().unwrap().extend(events);
+            }
+
+            let Some(callback) = firing.callback else {
+                continue;
+            };
+
+            for _ in 0..firing.count {
+                let result = (callback.lock().unwrap())(Arc::clone(engine), firing.entity.as_ref());
+
+                if let Err(err) = result {
+                    let mut err = match &firing.entity {
+                        Some(entity) => err.with_entity(entity),
+                        None => err,
+                    };
+                    if let Ok(scene) = engine.scenes().get_current_scene_handle() {
+                        err = err.with_scene(scene);
+                    }
+
+                    if engine.handle_error(err) == ErrorAction::DisableSystem {
+                        self.cancel(firing.handle);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn counts_down_intervals() {
+        let mut remaining = 3;
+        assert_eq!(count_down(&mut remaining, 3, 2, false), (0, false));
+        assert_eq!(remaining, 1);
+        assert_eq!(count_down(&mut remaining, 3, 2, false), (1, true));
+
+        let mut remaining = SECOND;
+        assert_eq!(
+            count_down(&mut remaining, SECOND, SECOND * 5 / 2, true),
+            (2, false)
+        );
+        assert_eq!(remaining, SECOND / 2);
+
+        let mut remaining = Duration::ZERO;
+        assert_eq!(
+            count_down(&mut remaining, Duration::ZERO, SECOND, true),
+            (1, false)
+        );
+    }
+
+    #[test]
+    fn handles_are_unique_across_scenes() {
+        let (first, second) = (Timers::new(), Timers::new());
+
+        let handle = first.start(None, Timer::after(SECOND));
+        let other = second.start(None, Timer::after(SECOND));
+        assert_ne!(handle, other);
+
+        assert!(!second.cancel(handle));
+        assert!(first.cancel(handle));
+        assert!(!first.cancel(handle));
+    }
+
+    #[test]
+    fn entity_timers_move_with_their_entity() {
+        let (source, target) = (Timers::new(), Timers::new());
+        let entities = crate::ecs::EntityManager::new();
+        let (entity, new_entity) = (
+            entities.create_entity().unwrap(),
+            entities.create_entity().unwrap(),
+        );
+
+        let moved = source.start(Some(entity.clone()), Timer::after(SECOND).repeating());
+        let scene_timer = source.start(None, Timer::after(SECOND));
+        source.set_paused(moved, true);
+
+        source.transfer_entity_timers(&entity, &target, &new_entity);
+
+        assert_eq!(source.remaining(moved), None);
+        assert_eq!(
+            source.remaining(scene_timer),
+            Some(TimerInterval::Time(SECOND))
+        );
+        assert_eq!(target.remaining(moved), Some(TimerInterval::Time(SECOND)));
+
+        let timers = target.timers.lock().unwrap();
+        assert_eq!(timers[0].entity, Some(new_entity));
+        assert!(timers[0].is_paused);
+    }
+}
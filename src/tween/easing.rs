@@ -0,0 +1,108 @@
+use std::f32::consts::PI;
+
+/// A curve mapping the progress of a tween to how far its value has moved, see
+/// easings.net for how each curve looks
+///
+/// In curves start slow, out curves end slow and in-out curves do both. Back and elastic
+/// curves overshoot the end values before settling on them
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    BounceIn,
+    BounceOut,
+}
+
+/// How far back curves overshoot
+const BACK_OVERSHOOT: f32 = 1.70158;
+
+impl Easing {
+    /// Eases a progress between 0 and 1, progress outside of that range is clamped
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t).powi(2),
+            Easing::QuadInOut => in_out(t, |t| t * t),
+            Easing::CubicIn => t.powi(3),
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => in_out(t, |t| t.powi(3)),
+            Easing::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            Easing::SineOut => (t * PI / 2.0).sin(),
+            Easing::SineInOut => (1.0 - (t * PI).cos()) / 2.0,
+            Easing::ExpoIn => expo_in(t),
+            Easing::ExpoOut => 1.0 - expo_in(1.0 - t),
+            Easing::ExpoInOut => in_out(t, expo_in),
+            Easing::BackIn => back_in(t),
+            Easing::BackOut => 1.0 - back_in(1.0 - t),
+            Easing::BackInOut => in_out(t, back_in),
+            Easing::ElasticIn => 1.0 - elastic_out(1.0 - t),
+            Easing::ElasticOut => elastic_out(t),
+            Easing::BounceIn => 1.0 - bounce_out(1.0 - t),
+            Easing::BounceOut => bounce_out(t),
+        }
+    }
+}
+
+/// Plays an in curve over the first half and its mirror over the second half
+fn in_out(t: f32, ease_in: fn(f32) -> f32) -> f32 {
+    match t < 0.5 {
+        true => ease_in(t * 2.0) / 2.0,
+        false => 1.0 - ease_in((1.0 - t) * 2.0) / 2.0,
+    }
+}
+
+fn expo_in(t: f32) -> f32 {
+    match t {
+        0.0 => 0.0,
+        _ => 2f32.powf(10.0 * t - 10.0),
+    }
+}
+
+fn back_in(t: f32) -> f32 {
+    (BACK_OVERSHOOT + 1.0) * t.powi(3) - BACK_OVERSHOOT * t * t
+}
+
+fn elastic_out(t: f32) -> f32 {
+    match t {
+        0.0 | 1.0 => t,
+        _ => 2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0,
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
@@ -0,0 +1,604 @@
+mod easing;
+
+use std::{fmt, marker::PhantomData, sync::Arc, time::Duration};
+
+use crate::{ecs, Color, Engine, Entity, ParallelSystem, Transform};
+pub use easing::Easing;
+
+/// A value that can be interpolated between two others
+pub trait Lerp {
+    /// The value a fraction t of the way from self to the other value, t can be outside
+    /// of 0 and 1 for curves that overshoot
+    fn lerp(&self, to: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for f64 {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        self + (to - self) * t as f64
+    }
+}
+
+impl Lerp for (f32, f32) {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        (self.0.lerp(&to.0, t), self.1.lerp(&to.1, t))
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        let channel = |from: u8, to: u8| (from as f32).lerp(&(to as f32), t).round() as u8;
+
+        Color {
+            r: channel(self.r, to.r),
+            g: channel(self.g, to.g),
+            b: channel(self.b, to.b),
+            a: channel(self.a, to.a),
+        }
+    }
+}
+
+impl Lerp for Transform {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        Transform {
+            x: self.x.lerp(&to.x, t),
+            y: self.y.lerp(&to.y, t),
+            rotation: self.rotation.lerp(&to.rotation, t),
+            scale_x: self.scale_x.lerp(&to.scale_x, t),
+            scale_y: self.scale_y.lerp(&to.scale_y, t),
+        }
+    }
+}
+
+/// Sets the tweened field of a component to its value at an eased progress
+type Apply<C> = Arc<dyn Fn(&mut C, f32) + Send + Sync>;
+
+/// A part of a tween's sequence, moving one value from its start to its end
+struct Step<C> {
+    apply: Apply<C>,
+    delay: Duration,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl<C> Clone for Step<C> {
+    fn clone(&self) -> Self {
+        Self {
+            apply: Arc::clone(&self.apply),
+            delay: self.delay,
+            duration: self.duration,
+            easing: self.easing,
+        }
+    }
+}
+
+/// Interpolates a field of the entity's C component over time, advanced by the TweenSystem
+///
+/// A tween is a sequence of steps played one after another, each with its own delay and
+/// easing. A cycle plays the sequence forwards, or forwards and then backwards for yoyo
+/// tweens, and the tween finishes once it played all of its cycles
+///
+/// ```ignore
+/// // fades a glyph in, turns it yellow after a pause and then fires "shown"
+/// let tween = Tween::new(Color::TRANSPARENT, Color::WHITE, fade, |glyph: &mut Glyph, color| {
+///     glyph.color = color
+/// })
+/// .with_easing(Easing::QuadOut)
+/// .then(
+///     Tween::new(Color::WHITE, Color::YELLOW, pulse, |glyph: &mut Glyph, color| {
+///         glyph.color = color
+///     })
+///     .with_delay(Duration::from_millis(200)),
+/// )
+/// .with_event("shown");
+/// ```
+pub struct Tween<C> {
+    steps: Vec<Step<C>>,
+    is_yoyo: bool,
+    /// how many times the tween plays again after its first cycle, None repeats it forever
+    repeat: Option<u32>,
+    event: Option<String>,
+    /// how far into the current cycle the tween is
+    elapsed: Duration,
+    /// how many cycles the tween has played
+    cycles: u32,
+    is_paused: bool,
+    is_finished: bool,
+    just_finished: bool,
+}
+
+impl<C> Tween<C> {
+    /// Creates a tween that moves a value from one end to another over the duration,
+    /// passing it to set every frame
+    pub fn new<V, F>(from: V, to: V, duration: Duration, set: F) -> Self
+    where
+        V: Lerp + Send + Sync + 'static,
+        F: Fn(&mut C, V) + Send + Sync + 'static,
+    {
+        let apply = move |component: &mut C, t: f32| set(component, from.lerp(&to, t));
+
+        Self {
+            steps: vec![Step {
+                apply: Arc::new(apply),
+                delay: Duration::ZERO,
+                duration,
+                easing: Easing::Linear,
+            }],
+            is_yoyo: false,
+            repeat: Some(0),
+            event: None,
+            elapsed: Duration::ZERO,
+            cycles: 0,
+            is_paused: false,
+            is_finished: false,
+            just_finished: false,
+        }
+    }
+
+    /// Sets the easing of the last step of the sequence
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        if let Some(step) = self.steps.last_mut() {
+            step.easing = easing;
+        }
+        self
+    }
+
+    /// Waits before playing the last step of the sequence
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        if let Some(step) = self.steps.last_mut() {
+            step.delay = delay;
+        }
+        self
+    }
+
+    /// Plays the steps of another tween once this tween's steps have finished
+    ///
+    /// Only the steps are added, the other tween's yoyo, repeats and event are ignored
+    pub fn then(mut self, next: Tween<C>) -> Self {
+        self.steps.extend(next.steps);
+        self
+    }
+
+    /// Plays the sequence backwards after every time it's played forwards
+    pub fn with_yoyo(mut self) -> Self {
+        self.is_yoyo = true;
+        self
+    }
+
+    /// Plays the tween again the given number of times after it's first played
+    pub fn with_repeat(mut self, times: u32) -> Self {
+        self.repeat = Some(times);
+        self
+    }
+
+    /// Plays the tween again every time it ends, it never finishes
+    pub fn with_infinite_repeat(mut self) -> Self {
+        self.repeat = None;
+        self
+    }
+
+    /// Fires an event when the tween finishes
+    pub fn with_event(mut self, event: &str) -> Self {
+        self.event = Some(event.to_owned());
+        self
+    }
+
+    /// Plays the tween again from the start of its sequence
+    pub fn restart(&mut self) {
+        self.elapsed = Duration::ZERO;
+        self.cycles = 0;
+        self.is_finished = false;
+        self.just_finished = false;
+    }
+
+    pub fn pause(&mut self) {
+        self.is_paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.is_paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.is_finished
+    }
+
+    /// Checks if the last advance finished the tween
+    pub fn just_finished(&self) -> bool {
+        self.just_finished
+    }
+
+    /// Checks if the last advance finished the tween and fired the event
+    pub fn has_event(&self, event: &str) -> bool {
+        self.just_finished && self.event.as_deref() == Some(event)
+    }
+
+    /// How long it takes to play the sequence forwards once, including delays
+    pub fn duration(&self) -> Duration {
+        self.steps
+            .iter()
+            .map(|step| step.delay + step.duration)
+            .sum()
+    }
+
+    /// Moves the tween forward by dt and sets the fields of the component it passed along the way
+    ///
+    /// Steps that are passed over within a single advance are still set to their end values,
+    /// so a long frame doesn't leave a field partway through a step. A tween without a
+    /// duration finishes on its first advance, even if it repeats forever
+    pub fn advance(&mut self, component: &mut C, dt: Duration) {
+        self.just_finished = false;
+        if self.is_paused || self.is_finished {
+            return;
+        }
+
+        let cycle_duration = match self.is_yoyo {
+            true => self.duration() * 2,
+            false => self.duration(),
+        };
+
+        let elapsed = self.elapsed + dt;
+        if elapsed < cycle_duration {
+            self.seek_cycle(component, self.elapsed, elapsed);
+            self.elapsed = elapsed;
+            return;
+        }
+
+        self.seek_cycle(component, self.elapsed, cycle_duration);
+        self.cycles = self.cycles.saturating_add(1);
+        let elapsed = elapsed - cycle_duration;
+
+        let cycles_left = self
+            .repeat
+            .map(|repeat| u64::from(repeat) + 1 - u64::from(self.cycles));
+        if cycle_duration.is_zero() || cycles_left == Some(0) {
+            self.finish();
+            return;
+        }
+
+        // every whole cycle passed over ends the same way, so they are only counted
+        // and the end of a cycle is set once, however many of them there were
+        let whole_cycles = elapsed.as_nanos() / cycle_duration.as_nanos();
+        if whole_cycles > 0 {
+            self.seek_cycle(component, Duration::ZERO, cycle_duration);
+        }
+        if cycles_left.is_some_and(|left| whole_cycles >= u128::from(left)) {
+            self.finish();
+            return;
+        }
+        self.cycles = self
+            .cycles
+            .saturating_add(u32::try_from(whole_cycles).unwrap_or(u32::MAX));
+
+        // the remainder is shorter than a cycle, so it fits in a u64 of nanoseconds
+        let elapsed = Duration::from_nanos((elapsed.as_nanos() % cycle_duration.as_nanos()) as u64);
+        self.seek_cycle(component, Duration::ZERO, elapsed);
+        self.elapsed = elapsed;
+    }
+
+    fn finish(&mut self) {
+        self.elapsed = Duration::ZERO;
+        self.is_finished = true;
+        self.just_finished = true;
+    }
+
+    /// Moves between two times of a cycle, folding the backwards half of yoyo tweens
+    /// onto the sequence
+    fn seek_cycle(&self, component: &mut C, from: Duration, to: Duration) {
+        let duration = self.duration();
+
+        if from <= duration {
+            self.seek(component, from, to.min(duration));
+        }
+        if to > duration {
+            self.seek(
+                component,
+                duration * 2 - from.max(duration),
+                duration * 2 - to,
+            );
+        }
+    }
+
+    /// Sets every step between two times of the sequence, in the order they are passed
+    fn seek(&self, component: &mut C, from: Duration, to: Duration) {
+        let (low, high) = (from.min(to), from.max(to));
+
+        let mut start = Duration::ZERO;
+        let mut spans = Vec::with_capacity(self.steps.len());
+        for step in &self.steps {
+            let begin = start + step.delay;
+            start = begin + step.duration;
+            spans.push((step, begin, start));
+        }
+        if to < from {
+            spans.reverse();
+        }
+
+        for (step, begin, end) in spans {
+            if high < begin || low > end {
+                continue;
+            }
+
+            let t = match step.duration.is_zero() {
+                true => (to >= begin) as u8 as f32,
+                false => {
+                    (to.saturating_sub(begin).as_secs_f32() / step.duration.as_secs_f32()).min(1.0)
+                }
+            };
+            (step.apply)(component, step.easing.apply(t));
+        }
+    }
+}
+
+impl<C> Clone for Tween<C> {
+    fn clone(&self) -> Self {
+        Self {
+            steps: self.steps.clone(),
+            is_yoyo: self.is_yoyo,
+            repeat: self.repeat,
+            event: self.event.clone(),
+            elapsed: self.elapsed,
+            cycles: self.cycles,
+            is_paused: self.is_paused,
+            is_finished: self.is_finished,
+            just_finished: self.just_finished,
+        }
+    }
+}
+
+impl<C> fmt::Debug for Tween<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tween")
+            .field("steps", &self.steps.len())
+            .field("is_yoyo", &self.is_yoyo)
+            .field("repeat", &self.repeat)
+            .field("event", &self.event)
+            .field("elapsed", &self.elapsed)
+            .field("cycles", &self.cycles)
+            .field("is_paused", &self.is_paused)
+            .field("is_finished", &self.is_finished)
+            .finish()
+    }
+}
+
+/// Advances every Tween<C> by the frame's dt and applies it to the entity's C component
+///
+/// Register one for every component type that is tweened, with Tween<C> and C as its
+/// signature
+pub struct TweenSystem<C> {
+    component: PhantomData<fn() -> C>,
+}
+
+impl<C> TweenSystem<C> {
+    pub fn new() -> Self {
+        Self {
+            component: PhantomData,
+        }
+    }
+}
+
+impl<C> Default for TweenSystem<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> Clone for TweenSystem<C> {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<C> fmt::Debug for TweenSystem<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TweenSystem<{}>", std::any::type_name::<C>())
+    }
+}
+
+impl<C: Send + 'static> ParallelSystem for TweenSystem<C> {
+    fn on_frame(
+        &self,
+        engine: Arc<Engine>,
+        entity: Entity,
+        dt: Duration,
+    ) -> Result<(), ecs::Error> {
+        let scene = engine.scenes().get_current_scene()?;
+
+        let mut tween = scene.get_component::<Tween<C>>(&entity)?;
+        let mut component = scene.get_component::<C>(&entity)?;
+
+        tween.advance(&mut component, dt);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    const STEP: Duration = Duration::from_millis(50);
+
+    /// Advances the tween by a step at a time, returning the value after every step
+    fn play(tween: &mut Tween<f32>, steps: usize) -> Vec<f32> {
+        let mut value = 0.0;
+        (0..steps)
+            .map(|_| {
+                tween.advance(&mut value, STEP);
+                value
+            })
+            .collect()
+    }
+
+    fn tween(from: f32, to: f32, duration: Duration) -> Tween<f32> {
+        Tween::new(from, to, duration, |value: &mut f32, to| *value = to)
+    }
+
+    #[test]
+    fn plays_sequences_with_delays() {
+        let mut tween = tween(0.0, 10.0, STEP * 2)
+            .then(tween(10.0, 20.0, STEP * 2).with_delay(STEP))
+            .with_event("done");
+        assert_eq!(tween.duration(), STEP * 5);
+
+        assert_eq!(play(&mut tween, 5), [5.0, 10.0, 10.0, 15.0, 20.0]);
+        assert!(tween.is_finished() && tween.has_event("done"));
+
+        // a finished tween no longer sets the value
+        assert_eq!(play(&mut tween, 1), [0.0]);
+        assert!(!tween.just_finished());
+    }
+
+    #[test]
+    fn yoyo_plays_backwards_and_repeats() {
+        let mut tween = tween(0.0, 10.0, STEP * 2)
+            .then(tween(10.0, 20.0, STEP * 2).with_delay(STEP))
+            .with_yoyo()
+            .with_repeat(1);
+
+        let values = play(&mut tween, 20);
+        let cycle = [5.0, 10.0, 10.0, 15.0, 20.0, 15.0, 10.0, 10.0, 5.0, 0.0];
+        assert_eq!(values, [cycle, cycle].concat());
+        assert!(tween.just_finished());
+    }
+
+    /// A step from one value to another that logs every value it is set to
+    fn logged_step(log: &Arc<Mutex<Vec<f32>>>, from: f32, to: f32) -> Tween<f32> {
+        let log = Arc::clone(log);
+        Tween::new(from, to, STEP, move |_: &mut f32, value: f32| {
+            log.lock().unwrap().push(value)
+        })
+    }
+
+    #[test]
+    fn long_frames_set_skipped_steps_to_their_ends() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut tween = logged_step(&log, 0.0, 1.0)
+            .then(logged_step(&log, 10.0, 11.0))
+            .then(logged_step(&log, 20.0, 21.0));
+
+        // every step passed is set once, to where it is at the end of the frame
+        tween.advance(&mut 0.0, STEP * 5 / 2);
+        assert_eq!(*log.lock().unwrap(), [1.0, 11.0, 20.5]);
+    }
+
+    #[test]
+    fn seeks_back_through_skipped_steps_in_reverse() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut tween = logged_step(&log, 0.0, 1.0)
+            .then(logged_step(&log, 10.0, 11.0))
+            .with_yoyo();
+
+        tween.advance(&mut 0.0, STEP * 2);
+        log.lock().unwrap().clear();
+
+        // the frame ends the forwards half and then passes the last step back to its start
+        tween.advance(&mut 0.0, STEP * 3 / 2);
+        assert_eq!(*log.lock().unwrap(), [11.0, 10.0, 0.5]);
+    }
+
+    #[test]
+    fn infinite_repeats_never_finish() {
+        let mut tween = tween(0.0, 10.0, STEP * 2).with_infinite_repeat();
+
+        assert_eq!(play(&mut tween, 6), [5.0, 0.0, 5.0, 0.0, 5.0, 0.0]);
+        assert!(!tween.is_finished());
+    }
+
+    #[test]
+    fn long_frames_skip_whole_cycles_at_once() {
+        let mut tween = tween(0.0, 10.0, Duration::from_micros(2)).with_infinite_repeat();
+        let mut value = 0.0;
+
+        // ten million cycles in one frame
+        tween.advance(
+            &mut value,
+            Duration::from_secs(20) + Duration::from_micros(1),
+        );
+        assert_eq!(value, 5.0);
+        assert!(!tween.is_finished());
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut tween = logged_step(&log, 0.0, 1.0)
+            .then(logged_step(&log, 10.0, 11.0))
+            .with_infinite_repeat();
+
+        // the current cycle and the skipped ones are each ended once
+        tween.advance(&mut 0.0, STEP * 2000 + STEP / 2);
+        assert_eq!(*log.lock().unwrap(), [1.0, 11.0, 1.0, 11.0, 0.5]);
+    }
+
+    #[test]
+    fn long_frames_finish_the_last_repeat() {
+        let mut yoyo = tween(0.0, 10.0, STEP * 2).with_yoyo().with_repeat(2);
+        let mut value = 0.0;
+
+        yoyo.advance(&mut value, STEP * 9);
+        assert_eq!(value, 5.0);
+        assert!(!yoyo.is_finished());
+
+        yoyo.advance(&mut value, STEP * 2);
+        assert_eq!(value, 5.0);
+        assert!(!yoyo.is_finished());
+        yoyo.advance(&mut value, STEP);
+        assert_eq!(value, 0.0);
+        assert!(yoyo.just_finished());
+
+        let mut repeating = tween(0.0, 10.0, STEP * 2).with_repeat(3);
+        repeating.advance(&mut value, STEP * 1000);
+        assert_eq!(value, 10.0);
+        assert!(repeating.just_finished());
+    }
+
+    #[test]
+    fn tweens_without_a_duration_finish_immediately() {
+        let mut tween = tween(0.0, 10.0, Duration::ZERO).with_infinite_repeat();
+
+        assert_eq!(play(&mut tween, 1), [10.0]);
+        assert!(tween.just_finished());
+    }
+
+    #[test]
+    fn paused_tweens_stay_where_they_are() {
+        let mut tween = tween(0.0, 10.0, STEP * 4);
+        assert_eq!(play(&mut tween, 1), [2.5]);
+
+        tween.pause();
+        assert_eq!(play(&mut tween, 2), [0.0, 0.0]);
+
+        tween.resume();
+        assert_eq!(play(&mut tween, 1), [5.0]);
+    }
+
+    #[test]
+    fn easing_starts_and_ends_on_the_tweened_values() {
+        let easings = [
+            Easing::Linear,
+            Easing::QuadInOut,
+            Easing::CubicOut,
+            Easing::SineInOut,
+            Easing::ExpoInOut,
+            Easing::BackInOut,
+            Easing::ElasticOut,
+            Easing::BounceIn,
+        ];
+
+        for easing in easings {
+            assert!(easing.apply(0.0).abs() < 1e-6, "{easing:?}");
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6, "{easing:?}");
+        }
+        assert_eq!(Easing::QuadIn.apply(0.5), 0.25);
+        assert!(Easing::BackIn.apply(0.2) < 0.0);
+    }
+}